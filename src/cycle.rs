@@ -0,0 +1,142 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Where a repeatedly stepped state starts looping, counted in steps from the initial state,
+/// and how many steps each loop takes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cycle {
+    pub start: u64,
+    pub length: u64,
+}
+
+impl Cycle {
+    /// The earliest step which is in the same state as step `n`
+    pub fn equivalent_step(&self, n: u64) -> u64 {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+
+    /// The state after `n` steps, only simulating up to the end of the first loop
+    pub fn state_after<S, F>(&self, mut initial: S, mut step: F, n: u64) -> S
+    where
+        F: FnMut(&mut S),
+    {
+        for _ in 0..self.equivalent_step(n) {
+            step(&mut initial);
+        }
+        initial
+    }
+
+    pub fn value_after<S, F, V, FV>(&self, initial: S, step: F, n: u64, value: FV) -> V
+    where
+        F: FnMut(&mut S),
+        FV: Fn(&S) -> V,
+    {
+        value(&self.state_after(initial, step, n))
+    }
+}
+
+/// Find the cycle by remembering every state seen
+pub fn find_cycle<S, F>(initial: &S, step: F) -> Cycle
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&mut S),
+{
+    find_cycle_by_fingerprint(initial, step, |x| x.clone())
+}
+
+/// Find the cycle by remembering a fingerprint of every state seen rather than the state itself.
+/// Two different states with the same fingerprint will be treated as the same.
+pub fn find_cycle_by_fingerprint<S, K, F, FK>(initial: &S, mut step: F, fingerprint: FK) -> Cycle
+where
+    S: Clone,
+    K: Hash + Eq,
+    F: FnMut(&mut S),
+    FK: Fn(&S) -> K,
+{
+    let mut seen_before = HashMap::new();
+    let mut state = initial.clone();
+    for i in 0.. {
+        if let Some(index) = seen_before.insert(fingerprint(&state), i) {
+            return Cycle {
+                start: index,
+                length: i - index,
+            };
+        }
+        step(&mut state);
+    }
+    unreachable!()
+}
+
+/// Find the cycle with Brent's algorithm, which only ever holds two states at once
+pub fn find_cycle_brent<S, F>(initial: &S, mut step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: FnMut(&mut S),
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    step(&mut hare);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        step(&mut hare);
+        length += 1;
+    }
+
+    let mut start = 0;
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        step(&mut hare);
+    }
+    while tortoise != hare {
+        step(&mut tortoise);
+        step(&mut hare);
+        start += 1;
+    }
+    Cycle { start, length }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cycle::{find_cycle, find_cycle_brent, find_cycle_by_fingerprint, Cycle};
+
+    fn step(x: &mut u64) {
+        *x = (*x * *x + 1) % 255;
+    }
+
+    #[test]
+    fn algorithms_agree() {
+        for initial in [0, 3, 17, 100] {
+            let cycle = find_cycle(&initial, step);
+            assert_eq!(cycle, find_cycle_brent(&initial, step));
+            assert_eq!(cycle, find_cycle_by_fingerprint(&initial, step, |x| *x));
+        }
+    }
+
+    #[test]
+    fn state_after_matches_simulation() {
+        let cycle = find_cycle(&3, step);
+        let mut x = 3;
+        for n in 0..200 {
+            assert_eq!(cycle.state_after(3, step, n), x);
+            step(&mut x);
+        }
+        assert_eq!(
+            Cycle {
+                start: 2,
+                length: 3
+            }
+            .equivalent_step(1_000_000_000),
+            4
+        );
+    }
+}
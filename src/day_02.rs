@@ -1,142 +1,122 @@
-use core::panic;
+use std::collections::BTreeMap;
 
 use crate::TaskCompleter;
 
+/// Number of cubes of each colour, keyed by colour name
+type Cubes = BTreeMap<String, u32>;
+
 struct Game {
     id: u32,
-    highest_value: MaxValueMap,
+    draws: Vec<Cubes>,
 }
 
 impl Game {
     fn create(input: &str) -> Self {
-        let mut input = input.split(":");
-        let game_id_str = input.next().unwrap();
-        let game_run = input.next().unwrap();
-        let id;
-        if game_id_str.starts_with("Game ") {
-            id = game_id_str[5..].parse::<u32>().unwrap();
-        } else {
-            panic!("Invalid format");
-        }
+        let (game_id_str, game_run) = input.split_once(':').expect("Missing ':' after game id");
+        let id = game_id_str
+            .strip_prefix("Game ")
+            .and_then(|x| x.parse::<u32>().ok())
+            .unwrap_or_else(|| panic!("Invalid game id {}", game_id_str));
 
-        let mut highest_value = MaxValueMap::default();
-        for picked_colours in game_run.split(";") {
-            for colour in picked_colours.split(",") {
-                let mut colour_itr = colour.split(" ");
-                colour_itr.next();
-                let number = colour_itr.next().unwrap().parse::<u32>().unwrap();
-                let colour = colour_itr.next().unwrap();
-                match colour {
-                    "red" => highest_value.update(Colour::Red, number),
-                    "green" => highest_value.update(Colour::Green, number),
-                    "blue" => highest_value.update(Colour::Blue, number),
-                    _ => panic!("Invalid colour"),
+        let draws = game_run
+            .split(';')
+            .map(|picked_colours| {
+                let mut draw = Cubes::new();
+                for colour in picked_colours.split(',') {
+                    let (number, colour) = colour
+                        .trim()
+                        .split_once(' ')
+                        .unwrap_or_else(|| panic!("Invalid draw {}", colour));
+                    let number = number.parse::<u32>().unwrap();
+                    *draw.entry(colour.to_owned()).or_default() += number;
                 }
-            }
-        }
+                draw
+            })
+            .collect();
 
-        Self {
-            id: id,
-            highest_value: highest_value,
-        }
+        Self { id, draws }
     }
-}
 
-#[derive(Default, PartialEq, Debug)]
-struct MaxValueMap {
-    red: u32,
-    green: u32,
-    blue: u32,
-}
+    /// The first draw which took more of some colour than `bag` holds, along with its index
+    fn impossible_draw(&self, bag: &Cubes) -> Option<(usize, &Cubes)> {
+        self.draws.iter().enumerate().find(|(_, draw)| {
+            draw.iter()
+                .any(|(colour, number)| bag.get(colour).copied().unwrap_or(0) < *number)
+        })
+    }
 
-impl PartialOrd for MaxValueMap {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.red.partial_cmp(&other.red) {
-            Some(core::cmp::Ordering::Equal) => {}
-            Some(core::cmp::Ordering::Less) => {}
-            ord => return ord,
-        }
-        match self.green.partial_cmp(&other.green) {
-            Some(core::cmp::Ordering::Equal) => {}
-            Some(core::cmp::Ordering::Less) => {}
-            ord => return ord,
-        }
-        self.blue.partial_cmp(&other.blue)
+    fn is_possible(&self, bag: &Cubes) -> bool {
+        self.impossible_draw(bag).is_none()
     }
-}
 
-impl MaxValueMap {
-    fn update(&mut self, colour: Colour, number: u32) {
-        match colour {
-            Colour::Red => {
-                if self.red < number {
-                    self.red = number
-                }
-            }
-            Colour::Green => {
-                if self.green < number {
-                    self.green = number
-                }
-            }
-            Colour::Blue => {
-                if self.blue < number {
-                    self.blue = number
-                }
-            }
+    /// The fewest cubes of each of `colours` the bag could have held
+    fn minimal_bag(&self, colours: &[&str]) -> Cubes {
+        colours
+            .iter()
+            .map(|colour| {
+                let most = self
+                    .draws
+                    .iter()
+                    .filter_map(|draw| draw.get(*colour))
+                    .max()
+                    .copied()
+                    .unwrap_or(0);
+                (colour.to_string(), most)
+            })
+            .collect()
+    }
+
+    /// The fewest cubes of every colour seen in the game
+    fn minimal_bag_all(&self) -> Cubes {
+        let mut bag = Cubes::new();
+        for (colour, number) in self.draws.iter().flatten() {
+            let most = bag.entry(colour.clone()).or_default();
+            *most = (*most).max(*number);
         }
+        bag
     }
 
-    fn get_power(&self) -> u32 {
-        self.red * self.blue * self.green
+    fn power(&self, colours: &[&str]) -> u32 {
+        self.minimal_bag(colours).values().product()
     }
 }
 
-enum Colour {
-    Red,
-    Green,
-    Blue,
+fn parse_games(input: &str) -> Vec<Game> {
+    input
+        .lines()
+        .filter(|x| !x.is_empty())
+        .map(Game::create)
+        .collect()
+}
+
+fn possible_games<'a>(games: &'a [Game], bag: &'a Cubes) -> impl Iterator<Item = &'a Game> {
+    games.iter().filter(|x| x.is_possible(bag))
 }
 
 pub struct Task2;
 
 impl TaskCompleter for Task2 {
     fn do_task_1(&self) -> String {
-        let contents: Vec<u32> = include_str!("../input/two/input")
-            .split("\n")
-            .filter_map(|x| {
-                if x.is_empty() {
-                    None
-                } else {
-                    Some(Game::create(x))
-                }
-            })
-            .filter(|x| {
-                &x.highest_value
-                    <= &MaxValueMap {
-                        red: 12,
-                        green: 13,
-                        blue: 14,
-                    }
-            })
+        let games = parse_games(include_str!("../input/two/input"));
+        let bag = Cubes::from([
+            ("red".to_owned(), 12),
+            ("green".to_owned(), 13),
+            ("blue".to_owned(), 14),
+        ]);
+        possible_games(&games, &bag)
             .map(|x| x.id)
-            .collect();
-        contents.iter().sum::<u32>().to_string()
+            .sum::<u32>()
+            .to_string()
     }
 
     fn do_task_2(&self) -> String {
-        let contents: Vec<u32> = include_str!("../input/two/input")
-            .split("\n")
-            .filter_map(|x| {
-                if x.is_empty() {
-                    None
-                } else {
-                    Some(Game::create(x))
-                }
-            })
-            .map(|x| x.highest_value.get_power())
-            .collect();
-        contents.iter().sum::<u32>().to_string()
+        parse_games(include_str!("../input/two/input"))
+            .iter()
+            .map(|x| x.power(&["red", "green", "blue"]))
+            .sum::<u32>()
+            .to_string()
     }
+
     fn task_1_result(&self) -> Option<String> {
         Some("2162".to_owned())
     }
@@ -145,3 +125,42 @@ impl TaskCompleter for Task2 {
         Some("72513".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_02::{parse_games, possible_games, Cubes};
+
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn queries_on_example() {
+        let games = parse_games(EXAMPLE);
+        let bag = Cubes::from([
+            ("red".to_owned(), 12),
+            ("green".to_owned(), 13),
+            ("blue".to_owned(), 14),
+        ]);
+        let ids: Vec<u32> = possible_games(&games, &bag).map(|x| x.id).collect();
+        assert_eq!(ids, vec![1, 2, 5]);
+        assert_eq!(games[2].impossible_draw(&bag).unwrap().0, 0);
+        assert_eq!(games[3].impossible_draw(&bag).unwrap().0, 2);
+        let powers: Vec<u32> = games
+            .iter()
+            .map(|x| x.power(&["red", "green", "blue"]))
+            .collect();
+        assert_eq!(powers, vec![48, 12, 1560, 630, 36]);
+        assert_eq!(games[0].power(&["red", "blue"]), 24);
+    }
+
+    #[test]
+    fn other_colours() {
+        let games = parse_games("Game 7: 2 purple, 1 red; 5 purple");
+        assert_eq!(games[0].minimal_bag_all()["purple"], 5);
+        assert_eq!(games[0].power(&["purple", "red"]), 5);
+        assert!(!games[0].is_possible(&Cubes::from([("red".to_owned(), 1)])));
+    }
+}
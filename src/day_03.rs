@@ -1,179 +1,140 @@
-use std::collections::HashSet;
+use std::{collections::HashMap, iter::zip};
+
+use crate::{
+    grid::{Coord, Grid},
+    TaskCompleter,
+};
+
+#[derive(Debug, PartialEq)]
+struct PartNumber {
+    value: u32,
+    /// Coordinate of the leftmost digit
+    start: Coord,
+    len: usize,
+}
 
-use crate::TaskCompleter;
+impl PartNumber {
+    fn coords(&self) -> impl Iterator<Item = Coord> + '_ {
+        (0..self.len as i64).map(|x| Coord::new(self.start.x() + x, self.start.y()))
+    }
+}
 
-#[derive(Debug)]
-struct Grid {
-    height: usize,
-    width: usize,
-    cells: Vec<GridCell>,
-    numbers: Vec<u32>,
+#[derive(Debug, PartialEq)]
+struct Symbol {
+    symbol: char,
+    coord: Coord,
 }
 
-#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, PartialOrd, Ord)]
-struct NumberID(usize);
+/// The engine schematic as a graph, linking each number to the symbols next to it
+struct Schematic {
+    grid: Grid<char>,
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
+}
 
-impl Grid {
+fn is_symbol(c: &char) -> bool {
+    c != &'.' && !c.is_ascii_digit()
+}
+
+impl Schematic {
     fn create(input: &str) -> Self {
-        let lines: Vec<&str> = input.lines().collect();
-        let height = lines.len();
-        let width = lines[0].len();
-        let mut cells = Vec::with_capacity(height * width);
-        let mut numbers = Vec::new();
-
-        for line in lines {
-            assert_eq!(line.len(), width);
-            let mut number = 0;
-            for char in line.chars() {
-                cells.push(match char {
-                    '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                        // Stinky but I don't care
-                        number *= 10;
-                        number += char.to_digit(10).unwrap();
-                        GridCell::Number(NumberID(numbers.len()))
-                    }
-                    '*' => {
-                        if number != 0 {
-                            numbers.push(number);
-                            number = 0;
-                        }
-                        GridCell::Gear
-                    }
-                    '.' => {
-                        if number != 0 {
-                            numbers.push(number);
-                            number = 0;
-                        }
-                        GridCell::Dot
-                    }
-                    _ => {
-                        if number != 0 {
-                            numbers.push(number);
-                            number = 0;
-                        }
-                        GridCell::Punctuation
-                    }
-                })
+        let grid: Grid<char> = input.parse().unwrap();
+        let mut numbers = vec![];
+        for (y, row) in grid.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                let len = row[x..].iter().take_while(|c| c.is_ascii_digit()).count();
+                if len > 0 {
+                    let value = row[x..x + len].iter().collect::<String>().parse().unwrap();
+                    numbers.push(PartNumber {
+                        value,
+                        start: Coord::new(x as i64, y as i64),
+                        len,
+                    });
+                }
+                x += len.max(1);
             }
-            if number != 0 {
-                numbers.push(number);
+        }
+
+        let symbols: Vec<Symbol> = grid
+            .iter()
+            .filter(|(_, c)| is_symbol(c))
+            .map(|(coord, c)| Symbol { symbol: *c, coord })
+            .collect();
+
+        let symbol_at: HashMap<Coord, usize> = zip(0.., symbols.iter())
+            .map(|(s, symbol)| (symbol.coord, s))
+            .collect();
+        let mut number_symbols = vec![];
+        let mut symbol_numbers = vec![vec![]; symbols.len()];
+        for (n, number) in numbers.iter().enumerate() {
+            let mut touching: Vec<usize> = number
+                .coords()
+                .flat_map(|c| grid.neighbours(c, true))
+                .filter_map(|(c, _)| symbol_at.get(&c).copied())
+                .collect();
+            touching.sort();
+            touching.dedup();
+            for s in touching.iter() {
+                symbol_numbers[*s].push(n);
             }
+            number_symbols.push(touching);
         }
-        Grid {
-            width,
-            height,
-            cells,
+
+        Self {
+            grid,
             numbers,
+            symbols,
+            number_symbols,
+            symbol_numbers,
         }
     }
 
-    fn get_index(&self, height: usize, width: usize) -> &GridCell {
-        &self.cells[(height * self.width) + width]
+    fn numbers_adjacent_to(&self, symbol: usize) -> impl Iterator<Item = &PartNumber> {
+        self.symbol_numbers[symbol]
+            .iter()
+            .map(|n| &self.numbers[*n])
     }
 
-    fn get_sum(&mut self) -> (u32, u32) {
-        let mut numbers = HashSet::new();
-        let mut gear_sum = 0;
-        for height in 0..self.height {
-            for width in 0..self.width {
-                match self.get_index(height, width) {
-                    GridCell::Gear => {
-                        let mut neighbours = Vec::new();
-                        self.get_neighbours(height, width).for_each(|x| {
-                            match x {
-                                GridCell::Number(n) => {
-                                    neighbours.push(*n);
-                                    numbers.insert(*n);
-                                }
-                                _ => (),
-                            };
-                        });
-                        neighbours.sort();
-                        neighbours.dedup();
-                        if neighbours.len() == 2 {
-                            gear_sum +=
-                                self.numbers[neighbours[0].0] * self.numbers[neighbours[1].0];
-                        }
-                    }
-                    GridCell::Punctuation => self.get_neighbours(height, width).for_each(|x| {
-                        match x {
-                            GridCell::Number(n) => {
-                                numbers.insert(*n);
-                            }
-                            _ => (),
-                        };
-                    }),
-                    _ => (),
-                };
-            }
-        }
-        (
-            numbers.iter().map(|x| self.numbers[x.0]).sum::<u32>(),
-            gear_sum,
-        )
+    /// Indices of symbols drawn as `c`
+    fn symbols_of(&self, c: char) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len()).filter(move |s| self.symbols[*s].symbol == c)
     }
 
-    fn get_neighbours<'a>(&'a self, height: usize, width: usize) -> GridCellNeighbours<'a> {
-        GridCellNeighbours {
-            height,
-            width,
-            index: 0,
-            grid: &self,
-        }
+    /// Indices of symbols touching exactly `k` numbers
+    fn symbols_with_neighbours(&self, k: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.symbols.len()).filter(move |s| self.symbol_numbers[*s].len() == k)
     }
-}
 
-#[derive(PartialEq, Debug)]
-enum GridCell {
-    Dot,
-    Punctuation,
-    Gear,
-    Number(NumberID),
-}
+    fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        (0..self.numbers.len())
+            .filter(|n| !self.number_symbols[*n].is_empty())
+            .map(|n| &self.numbers[n])
+    }
 
-struct GridCellNeighbours<'a> {
-    height: usize,
-    width: usize,
-    index: usize,
-    grid: &'a Grid,
-}
+    fn numbers_touching_no_symbol(&self) -> impl Iterator<Item = &PartNumber> {
+        (0..self.numbers.len())
+            .filter(|n| self.number_symbols[*n].is_empty())
+            .map(|n| &self.numbers[n])
+    }
 
-impl<'a> Iterator for GridCellNeighbours<'a> {
-    type Item = &'a GridCell;
+    fn gear_ratios(&self) -> impl Iterator<Item = u32> + '_ {
+        self.symbols_of('*')
+            .filter(|s| self.symbol_numbers[*s].len() == 2)
+            .map(|s| self.numbers_adjacent_to(s).map(|n| n.value).product())
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= 8 {
-            return None;
-        }
-        let mut height;
-        let mut width;
-        loop {
-            let (x, y) = [
-                (1, 1),
-                (1, 0),
-                (1, -1),
-                (0, 1),
-                (0, -1),
-                (-1, -1),
-                (-1, 0),
-                (-1, 1),
-            ][self.index];
-            height = self.height as i32 + y;
-            width = self.width as i32 + x;
-            if height >= 0
-                && height < self.grid.height as i32
-                && width >= 0
-                && width < self.grid.width as i32
-            {
-                self.index += 1;
-                break;
-            }
-            self.index += 1;
-            if self.index >= 8 {
-                return None;
-            }
+    /// The schematic as text with part numbers in bold green
+    fn render(&self) -> String {
+        let mut highlighted = self.grid.map(|c| c.to_string());
+        for number in self.part_numbers() {
+            let end = Coord::new(number.start.x() + number.len as i64 - 1, number.start.y());
+            highlighted[number.start] = format!("\x1b[1;32m{}", highlighted[number.start]);
+            highlighted[end] = format!("{}\x1b[0m", highlighted[end]);
         }
-        Some(self.grid.get_index(height as usize, width as usize))
+        highlighted.to_string()
     }
 }
 
@@ -181,19 +142,19 @@ pub struct Task3;
 
 impl TaskCompleter for Task3 {
     fn do_task_1(&self) -> String {
-        let mut grid = Grid::create(include_str!("../input/three/input"));
+        let schematic = Schematic::create(include_str!("../input/three/input"));
 
-        let (sum, _gear_sum) = grid.get_sum();
-
-        sum.to_string()
+        schematic
+            .part_numbers()
+            .map(|x| x.value)
+            .sum::<u32>()
+            .to_string()
     }
 
     fn do_task_2(&self) -> String {
-        let mut grid = Grid::create(include_str!("../input/three/input"));
-
-        let (_sum, gear_sum) = grid.get_sum();
+        let schematic = Schematic::create(include_str!("../input/three/input"));
 
-        gear_sum.to_string()
+        schematic.gear_ratios().sum::<u32>().to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -204,3 +165,44 @@ impl TaskCompleter for Task3 {
         Some("87449461".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_03::Schematic;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn graph_queries() {
+        let schematic = Schematic::create(EXAMPLE);
+        assert_eq!(schematic.part_numbers().map(|x| x.value).sum::<u32>(), 4361);
+        assert_eq!(schematic.gear_ratios().sum::<u32>(), 467835);
+        let lonely: Vec<u32> = schematic
+            .numbers_touching_no_symbol()
+            .map(|x| x.value)
+            .collect();
+        assert_eq!(lonely, vec![114, 58]);
+        let first_star = schematic.symbols_of('*').next().unwrap();
+        let around: Vec<u32> = schematic
+            .numbers_adjacent_to(first_star)
+            .map(|x| x.value)
+            .collect();
+        assert_eq!(around, vec![467, 35]);
+        assert_eq!(schematic.symbols_with_neighbours(1).count(), 4);
+    }
+
+    #[test]
+    fn render_highlights_part_numbers() {
+        let schematic = Schematic::create("12.3\n*...");
+        assert_eq!(schematic.render(), "\x1b[1;32m12\x1b[0m.3\n*...");
+    }
+}
@@ -1,48 +1,154 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashSet, VecDeque},
+    ops::Range,
+};
 
 use crate::TaskCompleter;
 
 fn parse_numbers(numbers: &str) -> HashSet<u32> {
-    let mut set = HashSet::new();
-    for num in numbers.split(" ") {
-        if !num.is_empty() {
-            set.insert(num.parse::<u32>().unwrap());
+    numbers
+        .split_whitespace()
+        .map(|num| num.parse::<u32>().unwrap())
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct Scratchcard {
+    id: u32,
+    winning: HashSet<u32>,
+    owned: HashSet<u32>,
+}
+
+impl Scratchcard {
+    fn new(line: &str) -> Self {
+        let (card, numbers) = line.split_once(':').expect("Missing ':' after card id");
+        let (winning, owned) = numbers.split_once('|').expect("Missing '|' in card");
+        Self {
+            id: card
+                .strip_prefix("Card")
+                .and_then(|x| x.trim().parse().ok())
+                .unwrap_or_else(|| panic!("Invalid card id {}", card)),
+            winning: parse_numbers(winning),
+            owned: parse_numbers(owned),
+        }
+    }
+
+    fn matches(&self) -> usize {
+        self.winning.intersection(&self.owned).count()
+    }
+
+    fn points(&self) -> u32 {
+        match self.matches() {
+            0 => 0,
+            x => 2_u32.pow(x as u32 - 1),
         }
     }
-    set
 }
 
-pub struct Task4;
+/// The puzzle's rule: a card at `index` with `matches` wins one copy of each of the next
+/// `matches` cards
+fn following_cards(index: usize, matches: usize) -> Range<usize> {
+    index + 1..index + 1 + matches
+}
 
-impl TaskCompleter for Task4 {
-    fn do_task_1(&self) -> String {
-        let contents = include_str!("../input/four/input");
-        let mut task_1_sum = 0;
-        for line in contents.lines() {
-            let _card_number = &line[5..8].trim().parse::<u32>().unwrap() - 1;
-            let winning_numbers = parse_numbers(&line[9..40]);
-            let my_numbers = parse_numbers(&line[41..]);
-            let winning_matches = winning_numbers.intersection(&my_numbers).count() as u32;
-            if winning_matches != 0 {
-                task_1_sum += (2 as u32).pow(winning_matches - 1);
+/// Like `following_cards` but never past the end of a table of `size` cards
+fn following_cards_within(size: usize) -> impl Fn(usize, usize) -> Range<usize> {
+    move |index, matches| {
+        let won = following_cards(index, matches);
+        won.start.min(size)..won.end.min(size)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct CascadeStats {
+    /// Total number of each card held at the end, including the original
+    copies: Vec<u64>,
+    /// For each card, which cards won copies of it and how many
+    won_from: Vec<Vec<(usize, u64)>>,
+}
+
+/// Play out every card, where `rule` gives the indices of the cards won by a card at some index
+/// with some number of matches
+fn cascade<F>(cards: &[Scratchcard], rule: F) -> CascadeStats
+where
+    F: Fn(usize, usize) -> Range<usize>,
+{
+    let mut copies = vec![1; cards.len()];
+    let mut won_from = vec![vec![]; cards.len()];
+    for (index, card) in cards.iter().enumerate() {
+        for won in rule(index, card.matches()) {
+            assert!(won > index, "Cards can only win copies of later cards");
+            if won < cards.len() {
+                copies[won] += copies[index];
+                won_from[won].push((index, copies[index]));
             }
         }
-        task_1_sum.to_string()
     }
+    CascadeStats { copies, won_from }
+}
 
-    fn do_task_2(&self) -> String {
-        let contents = include_str!("../input/four/input");
-        let mut card_copies = [1 as u32; 198];
-        for line in contents.lines() {
-            let card_number = &line[5..8].trim().parse::<u32>().unwrap() - 1;
-            let winning_numbers = parse_numbers(&line[9..40]);
-            let my_numbers = parse_numbers(&line[41..]);
-            let winning_matches = winning_numbers.intersection(&my_numbers).count() as u32;
-            for i in (card_number + 1)..(card_number + winning_matches + 1) {
-                card_copies[i as usize] += card_copies[card_number as usize];
+/// Yields each card along with how many copies of it are held, only remembering copies won for
+/// cards not yet reached. Copies won past the end of the input are dropped.
+struct CascadeStream<I, F> {
+    cards: I,
+    rule: F,
+    index: usize,
+    pending: VecDeque<u64>,
+}
+
+impl<I, F> CascadeStream<I, F> {
+    fn new(cards: I, rule: F) -> Self {
+        Self {
+            cards,
+            rule,
+            index: 0,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<I, F> Iterator for CascadeStream<I, F>
+where
+    I: Iterator<Item = Scratchcard>,
+    F: Fn(usize, usize) -> Range<usize>,
+{
+    type Item = (Scratchcard, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let card = self.cards.next()?;
+        let copies = 1 + self.pending.pop_front().unwrap_or(0);
+        for won in (self.rule)(self.index, card.matches()) {
+            assert!(won > self.index, "Cards can only win copies of later cards");
+            let offset = won - self.index - 1;
+            if self.pending.len() <= offset {
+                self.pending.resize(offset + 1, 0);
             }
+            self.pending[offset] += copies;
         }
-        card_copies.iter().sum::<u32>().to_string()
+        self.index += 1;
+        Some((card, copies))
+    }
+}
+
+pub struct Task4;
+
+impl TaskCompleter for Task4 {
+    fn do_task_1(&self) -> String {
+        include_str!("../input/four/input")
+            .lines()
+            .map(|x| Scratchcard::new(x).points())
+            .sum::<u32>()
+            .to_string()
+    }
+
+    fn do_task_2(&self) -> String {
+        let cards = include_str!("../input/four/input")
+            .lines()
+            .map(Scratchcard::new);
+        CascadeStream::new(cards, following_cards)
+            .map(|(_, copies)| copies)
+            .sum::<u64>()
+            .to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -53,3 +159,38 @@ impl TaskCompleter for Task4 {
         Some("8477787".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_04::{
+        cascade, following_cards, following_cards_within, CascadeStream, Scratchcard,
+    };
+
+    const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn cascade_on_example() {
+        let cards: Vec<Scratchcard> = EXAMPLE.lines().map(Scratchcard::new).collect();
+        assert_eq!(cards.iter().map(|x| x.points()).sum::<u32>(), 13);
+        let stats = cascade(&cards, following_cards);
+        assert_eq!(stats.copies, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(stats.won_from[2], vec![(0, 1), (1, 2)]);
+        let streamed: Vec<u64> = CascadeStream::new(cards.iter().cloned(), following_cards)
+            .map(|(_, copies)| copies)
+            .collect();
+        assert_eq!(streamed, stats.copies);
+    }
+
+    #[test]
+    fn capped_table() {
+        let cards: Vec<Scratchcard> = EXAMPLE.lines().map(Scratchcard::new).collect();
+        let stats = cascade(&cards, following_cards_within(3));
+        assert_eq!(stats.copies, vec![1, 2, 4, 1, 1, 1]);
+        assert_eq!(cards[3].id, 4);
+    }
+}
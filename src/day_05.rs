@@ -1,39 +1,85 @@
-use std::{cmp::max, ops::Range};
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+    fmt::Display,
+    ops::Range,
+};
 
 use crate::TaskCompleter;
 
+#[derive(Debug, PartialEq)]
+enum RangeMapError {
+    SourceOverlap(Range<u64>),
+    DestinationOverlap(Range<u64>),
+    /// Values in this range are reached from more than one source value
+    NotInvertible(Range<u64>),
+    /// A line that isn't three numbers
+    InvalidLine(String),
+}
+
+impl Display for RangeMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeMapError::SourceOverlap(x) => write!(f, "Source ranges overlap on {:?}", x),
+            RangeMapError::DestinationOverlap(x) => {
+                write!(f, "Destination ranges overlap on {:?}", x)
+            }
+            RangeMapError::NotInvertible(x) => {
+                write!(f, "Values {:?} are reached from more than one source", x)
+            }
+            RangeMapError::InvalidLine(x) => {
+                write!(f, "Expected a destination, source and length in '{}'", x)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
 struct RangeMap {
-    // Store special ranges
+    // Store special ranges as (source, dest, length), sorted by source value
     ranges: Vec<(u64, u64, u64)>,
 }
 
 impl RangeMap {
-    fn create(lines: &[&str]) -> Self {
-        let range = Vec::new();
-        let mut me = Self { ranges: range };
+    fn create(lines: &[&str]) -> Result<Self, RangeMapError> {
+        let mut me = Self::default();
         for line in lines {
-            let mut l = line.split(" ");
-            let dest = l.next().unwrap().parse::<u64>().unwrap();
-            let source = l.next().unwrap().parse::<u64>().unwrap();
-            let length = l.next().unwrap().parse::<u64>().unwrap();
-            me.insert_range(source, dest, length);
+            let numbers = line
+                .split_whitespace()
+                .map(|x| x.parse::<u64>())
+                .collect::<Result<Vec<_>, _>>();
+            let Ok(&[dest, source, length]) = numbers.as_deref() else {
+                return Err(RangeMapError::InvalidLine(line.to_string()));
+            };
+            me.insert_range(source, dest, length)?;
         }
-        // Ensure ranges sorted by source value
-        me.ranges.sort_by(|x, y| x.0.cmp(&y.0));
-        me
+        Ok(me)
     }
-    fn insert_range(&mut self, source_start: u64, dest_start: u64, length: u64) {
+
+    fn insert_range(
+        &mut self,
+        source_start: u64,
+        dest_start: u64,
+        length: u64,
+    ) -> Result<(), RangeMapError> {
         // Check if there is an overlapping range already
         for (x, y, l) in &self.ranges {
             let (x, y, l) = (*x, *y, *l);
             if x < source_start + length && source_start < x + l {
-                panic!("Source ranges overlap");
+                return Err(RangeMapError::SourceOverlap(
+                    max(x, source_start)..min(x + l, source_start + length),
+                ));
             }
             if y < dest_start + length && dest_start < y + l {
-                panic!("Destination ranges overlap");
+                return Err(RangeMapError::DestinationOverlap(
+                    max(y, dest_start)..min(y + l, dest_start + length),
+                ));
             }
         }
-        self.ranges.push((source_start, dest_start, length));
+        let index = self.ranges.partition_point(|x| x.0 < source_start);
+        self.ranges
+            .insert(index, (source_start, dest_start, length));
+        Ok(())
     }
 
     fn get_value(&self, key: u64) -> u64 {
@@ -52,7 +98,7 @@ impl RangeMap {
         // Ranges are sorted with respect to source value
         for (x, y, l) in &self.ranges {
             let (source, dest, length) = (*x, *y, *l);
-            if source + length < range.start || range.end <= source {
+            if source + length <= range.start || range.end <= source {
                 continue;
             }
             if last_end < source {
@@ -77,125 +123,287 @@ impl RangeMap {
         }
         new_ranges
     }
+
+    /// All values split into runs of (source, dest, length), including the unmapped gaps which
+    /// map to themselves
+    fn pieces(&self) -> Vec<(u64, u64, u64)> {
+        let mut pieces = vec![];
+        let mut next = 0;
+        for &(source, dest, length) in &self.ranges {
+            if next < source {
+                pieces.push((next, next, source - next));
+            }
+            pieces.push((source, dest, length));
+            next = source + length;
+        }
+        if next < u64::MAX {
+            pieces.push((next, next, u64::MAX - next));
+        }
+        pieces
+    }
+
+    /// Build from runs that may be given in any order, dropping any that map values to themselves
+    /// and joining runs that continue one another
+    fn from_pieces(mut pieces: Vec<(u64, u64, u64)>) -> Self {
+        pieces.sort();
+        let mut ranges: Vec<(u64, u64, u64)> = vec![];
+        for (source, dest, length) in pieces {
+            if source == dest {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some((s, d, l)) if *s + *l == source && *d + *l == dest => *l += length,
+                _ => ranges.push((source, dest, length)),
+            }
+        }
+        Self { ranges }
+    }
+
+    /// A single map equivalent to applying this map and then `then`
+    fn compose(&self, then: &RangeMap) -> RangeMap {
+        let after = then.pieces();
+        let mut pieces = vec![];
+        for (source, dest, length) in self.pieces() {
+            let first = after.partition_point(|(s, _, l)| s + l <= dest);
+            for &(s, d, l) in after[first..]
+                .iter()
+                .take_while(|(s, _, _)| *s < dest + length)
+            {
+                let start = max(dest, s);
+                let end = min(dest + length, s + l);
+                pieces.push((source + (start - dest), d + (start - s), end - start));
+            }
+        }
+        Self::from_pieces(pieces)
+    }
+
+    /// The map taking each value back to the one that maps to it, as long as no two values map
+    /// to the same place
+    fn invert(&self) -> Result<RangeMap, RangeMapError> {
+        let mut images: Vec<Range<u64>> = self
+            .pieces()
+            .into_iter()
+            .map(|(_, dest, length)| dest..dest + length)
+            .collect();
+        images.sort_by_key(|x| x.start);
+        for pair in images.windows(2) {
+            if pair[1].start < pair[0].end {
+                return Err(RangeMapError::NotInvertible(
+                    pair[1].start..min(pair[0].end, pair[1].end),
+                ));
+            }
+        }
+        Ok(Self::from_pieces(
+            self.pieces()
+                .into_iter()
+                .map(|(source, dest, length)| (dest, source, length))
+                .collect(),
+        ))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum AlmanacError {
+    MissingSeeds,
+    InvalidSeed(String),
+    InvalidHeader(String),
+    /// Two maps start from the same category
+    DuplicateCategory(String),
+    UnknownCategory(String),
+    NoPath {
+        from: String,
+        to: String,
+    },
+    BadMap {
+        category: String,
+        error: RangeMapError,
+    },
+    NotInvertible {
+        from: String,
+        to: String,
+    },
+}
+
+impl Display for AlmanacError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlmanacError::MissingSeeds => f.write_str("Almanac doesn't start with a seeds line"),
+            AlmanacError::InvalidSeed(x) => write!(f, "Invalid seed '{}'", x),
+            AlmanacError::InvalidHeader(x) => write!(f, "Invalid map header '{}'", x),
+            AlmanacError::DuplicateCategory(x) => {
+                write!(f, "More than one map from category '{}'", x)
+            }
+            AlmanacError::UnknownCategory(x) => write!(f, "No map mentions category '{}'", x),
+            AlmanacError::NoPath { from, to } => {
+                write!(f, "Can't get from category '{}' to '{}'", from, to)
+            }
+            AlmanacError::BadMap { category, error } => {
+                write!(f, "Map from category '{}' is invalid: {}", category, error)
+            }
+            AlmanacError::NotInvertible { from, to } => {
+                write!(f, "Can't map back from category '{}' to '{}'", to, from)
+            }
+        }
+    }
+}
+
+struct Almanac {
+    seeds: Vec<u64>,
+    // Keyed by source category, giving the destination category and the map to get there
+    maps: HashMap<String, (String, RangeMap)>,
 }
 
-fn get_contents() -> (
-    Vec<u64>,
-    Vec<Range<u64>>,
-    RangeMap,
-    RangeMap,
-    RangeMap,
-    RangeMap,
-    RangeMap,
-    RangeMap,
-    RangeMap,
-) {
-    let contents = include_str!("../input/five/input");
-    let lines: Vec<&str> = contents.lines().collect();
-    let seeds = lines[0]
-        .split(&[':', ' '])
-        .map(|x| x.trim().parse::<u64>())
-        .filter(|x| x.is_ok())
-        .map(|x| x.unwrap())
-        .collect();
-    let seed_to_soil = RangeMap::create(&lines[3..18]);
-    let soil_to_fertilizer = RangeMap::create(&lines[21..38]);
-    let fertilizer_to_water = RangeMap::create(&lines[41..81]);
-    let water_to_light = RangeMap::create(&lines[83..99]);
-    let light_to_temperature = RangeMap::create(&lines[101..140]);
-    let temperature_to_humidity = RangeMap::create(&lines[143..180]);
-    let humidity_to_location = RangeMap::create(&lines[184..218]);
-    let seed_ranges = lines[0]
-        .split(&[':', ' '])
-        .map(|x| x.trim().parse::<u64>())
-        .filter(|x| x.is_ok())
-        .map(|x| x.unwrap())
-        .fold(
-            (None, Vec::new()),
-            |(part_range, mut ranges), y| match part_range {
-                Some(n) => {
-                    assert!(n < n + y);
-                    ranges.push(n..n + y);
-                    (None, ranges)
-                }
-                None => (Some(y), ranges),
-            },
+impl Almanac {
+    fn parse(input: &str) -> Result<Self, AlmanacError> {
+        // Sections are separated by blank lines
+        let mut sections: Vec<Vec<&str>> = vec![vec![]];
+        for line in input.lines().map(|x| x.trim()) {
+            if line.is_empty() {
+                sections.push(vec![]);
+            } else {
+                sections.last_mut().unwrap().push(line);
+            }
+        }
+        let mut sections = sections.into_iter().filter(|x| !x.is_empty());
+        let seeds = sections
+            .next()
+            .map(|x| x.join(" "))
+            .as_deref()
+            .and_then(|x| x.strip_prefix("seeds:"))
+            .ok_or(AlmanacError::MissingSeeds)?
+            .split_whitespace()
+            .map(|x| {
+                x.parse::<u64>()
+                    .map_err(|_| AlmanacError::InvalidSeed(x.to_owned()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut maps = HashMap::new();
+        for lines in sections {
+            let (source, dest) = lines[0]
+                .strip_suffix(" map:")
+                .and_then(|x| x.split_once("-to-"))
+                .ok_or_else(|| AlmanacError::InvalidHeader(lines[0].to_owned()))?;
+            if maps.contains_key(source) {
+                return Err(AlmanacError::DuplicateCategory(source.to_owned()));
+            }
+            let map = RangeMap::create(&lines[1..]).map_err(|error| AlmanacError::BadMap {
+                category: source.to_owned(),
+                error,
+            })?;
+            maps.insert(source.to_owned(), (dest.to_owned(), map));
+        }
+        Ok(Self { seeds, maps })
+    }
+
+    fn has_category(&self, category: &str) -> bool {
+        self.maps.contains_key(category) || self.maps.values().any(|(dest, _)| dest == category)
+    }
+
+    /// The maps to apply in order to go from category `from` to category `to`
+    fn chain(&self, from: &str, to: &str) -> Result<Vec<&RangeMap>, AlmanacError> {
+        for category in [from, to] {
+            if !self.has_category(category) {
+                return Err(AlmanacError::UnknownCategory(category.to_owned()));
+            }
+        }
+        let mut chain = vec![];
+        let mut current = from;
+        while current != to {
+            let Some((dest, map)) = self.maps.get(current) else {
+                return Err(AlmanacError::NoPath {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                });
+            };
+            if chain.len() > self.maps.len() {
+                // Gone round a loop of categories without finding `to`
+                return Err(AlmanacError::NoPath {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                });
+            }
+            chain.push(map);
+            current = dest;
+        }
+        Ok(chain)
+    }
+
+    fn lookup(&self, from: &str, to: &str, value: u64) -> Result<u64, AlmanacError> {
+        Ok(self
+            .chain(from, to)?
+            .iter()
+            .fold(value, |x, map| map.get_value(x)))
+    }
+
+    fn lookup_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: Vec<Range<u64>>,
+    ) -> Result<Vec<Range<u64>>, AlmanacError> {
+        Ok(self.chain(from, to)?.iter().fold(ranges, |ranges, map| {
+            ranges.into_iter().flat_map(|x| map.map_range(x)).collect()
+        }))
+    }
+
+    /// A single map going straight from category `from` to category `to`
+    fn composed(&self, from: &str, to: &str) -> Result<RangeMap, AlmanacError> {
+        Ok(self
+            .chain(from, to)?
+            .iter()
+            .fold(RangeMap::default(), |composed, map| composed.compose(map)))
+    }
+
+    /// The value in category `from` that maps to `value` in category `to`
+    fn reverse_lookup(&self, from: &str, to: &str, value: u64) -> Result<u64, AlmanacError> {
+        let inverse =
+            self.composed(from, to)?
+                .invert()
+                .map_err(|_| AlmanacError::NotInvertible {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                })?;
+        Ok(inverse.get_value(value))
+    }
+
+    /// The seeds line read as pairs of range start and length
+    fn seed_ranges(&self) -> Vec<Range<u64>> {
+        assert!(
+            self.seeds.len().is_multiple_of(2),
+            "Seeds don't come in pairs"
         );
-    assert_eq!(seed_ranges.0, None);
-
-    (
-        seeds,
-        seed_ranges.1,
-        seed_to_soil,
-        soil_to_fertilizer,
-        fertilizer_to_water,
-        water_to_light,
-        light_to_temperature,
-        temperature_to_humidity,
-        humidity_to_location,
-    )
+        self.seeds.chunks(2).map(|x| x[0]..x[0] + x[1]).collect()
+    }
 }
 
 pub struct Task5;
 
 impl TaskCompleter for Task5 {
     fn do_task_1(&self) -> String {
-        let (
-            seeds,
-            _,
-            seed_to_soil,
-            soil_to_fertilizer,
-            fertilizer_to_water,
-            water_to_light,
-            light_to_temperature,
-            temperature_to_humidity,
-            humidity_to_location,
-        ) = get_contents();
-
-        seeds
-            .into_iter()
-            .map(|x| seed_to_soil.get_value(x))
-            .map(|x| soil_to_fertilizer.get_value(x))
-            .map(|x| fertilizer_to_water.get_value(x))
-            .map(|x| water_to_light.get_value(x))
-            .map(|x| light_to_temperature.get_value(x))
-            .map(|x| temperature_to_humidity.get_value(x))
-            .map(|x| humidity_to_location.get_value(x))
+        let almanac = Almanac::parse(include_str!("../input/five/input")).unwrap();
+        let map = almanac.composed("seed", "location").unwrap();
+
+        almanac
+            .seeds
+            .iter()
+            .map(|x| map.get_value(*x))
             .min()
             .unwrap()
             .to_string()
     }
 
     fn do_task_2(&self) -> String {
-        let (
-            _,
-            seed_ranges,
-            seed_to_soil,
-            soil_to_fertilizer,
-            fertilizer_to_water,
-            water_to_light,
-            light_to_temperature,
-            temperature_to_humidity,
-            humidity_to_location,
-        ) = get_contents();
-
-        seed_ranges
+        let almanac = Almanac::parse(include_str!("../input/five/input")).unwrap();
+        let map = almanac.composed("seed", "location").unwrap();
+
+        almanac
+            .seed_ranges()
             .into_iter()
-            .map(|x| seed_to_soil.map_range(x))
-            .flatten()
-            .map(|x| soil_to_fertilizer.map_range(x))
-            .flatten()
-            .map(|x| fertilizer_to_water.map_range(x))
-            .flatten()
-            .map(|x| water_to_light.map_range(x))
-            .flatten()
-            .map(|x| light_to_temperature.map_range(x))
-            .flatten()
-            .map(|x| temperature_to_humidity.map_range(x))
-            .flatten()
-            .map(|x| humidity_to_location.map_range(x))
-            .flatten()
-            .fold(u64::MAX, |x, y| if y.start < x { y.start } else { x })
+            .flat_map(|x| map.map_range(x))
+            .map(|x| x.start)
+            .min()
+            .unwrap()
             .to_string()
     }
 
@@ -207,3 +415,169 @@ impl TaskCompleter for Task5 {
         Some("24261545".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_05::{Almanac, AlmanacError, RangeMap, RangeMapError};
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn lookups_on_example() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+        assert_eq!(almanac.lookup("seed", "location", 79), Ok(82));
+        assert_eq!(almanac.lookup("seed", "humidity", 79), Ok(78));
+        assert_eq!(almanac.lookup("light", "location", 74), Ok(82));
+        let crlf = Almanac::parse(&EXAMPLE.replace('\n', "\r\n")).unwrap();
+        assert_eq!(crlf.seeds, almanac.seeds);
+        assert_eq!(crlf.lookup("seed", "location", 79), Ok(82));
+        let lowest = almanac
+            .lookup_ranges("seed", "location", almanac.seed_ranges())
+            .unwrap()
+            .iter()
+            .map(|x| x.start)
+            .min();
+        assert_eq!(lowest, Some(46));
+    }
+
+    #[test]
+    fn category_errors() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+        assert_eq!(
+            almanac.lookup("seed", "gravel", 1),
+            Err(AlmanacError::UnknownCategory("gravel".to_owned()))
+        );
+        assert_eq!(
+            almanac.lookup("location", "seed", 1),
+            Err(AlmanacError::NoPath {
+                from: "location".to_owned(),
+                to: "seed".to_owned()
+            })
+        );
+        assert_eq!(
+            Almanac::parse("seeds: 1\n\nseed to soil map:\n1 2 3").err(),
+            Some(AlmanacError::InvalidHeader("seed to soil map:".to_owned()))
+        );
+    }
+
+    #[test]
+    fn composed_matches_chain() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+        let map = almanac.composed("seed", "location").unwrap();
+        for seed in 0..150 {
+            assert_eq!(
+                Ok(map.get_value(seed)),
+                almanac.lookup("seed", "location", seed)
+            );
+        }
+        let lowest = almanac
+            .seed_ranges()
+            .into_iter()
+            .flat_map(|x| map.map_range(x))
+            .map(|x| x.start)
+            .min();
+        assert_eq!(lowest, Some(46));
+    }
+
+    #[test]
+    fn reverse_lookups() {
+        let almanac = Almanac::parse(EXAMPLE).unwrap();
+        assert_eq!(almanac.reverse_lookup("seed", "location", 46), Ok(82));
+        assert_eq!(almanac.reverse_lookup("seed", "location", 82), Ok(79));
+        let inverse = almanac
+            .composed("seed", "location")
+            .unwrap()
+            .invert()
+            .unwrap();
+        for location in 0..150 {
+            assert_eq!(
+                almanac.lookup("seed", "location", inverse.get_value(location)),
+                Ok(location)
+            );
+        }
+
+        // 98 and 99 map onto 50 and 51, which are also left in place
+        let map = RangeMap::create(&["50 98 2"]).unwrap();
+        assert_eq!(map.invert(), Err(RangeMapError::NotInvertible(50..52)));
+    }
+
+    #[test]
+    fn overlap_errors() {
+        assert_eq!(
+            RangeMap::create(&["50 98 2", "10 90 10"]),
+            Err(RangeMapError::SourceOverlap(98..100))
+        );
+        assert_eq!(
+            RangeMap::create(&["50 98 2", "49 10 2"]),
+            Err(RangeMapError::DestinationOverlap(50..51))
+        );
+        assert_eq!(
+            Almanac::parse("seeds: 1\n\nseed-to-soil map:\n1 2 3\n2 3 3").err(),
+            Some(AlmanacError::BadMap {
+                category: "seed".to_owned(),
+                error: RangeMapError::SourceOverlap(3..5)
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_input_errors() {
+        for line in ["50 98", "50 98 2 1", "50 x 2", ""] {
+            assert_eq!(
+                RangeMap::create(&[line]),
+                Err(RangeMapError::InvalidLine(line.to_owned()))
+            );
+        }
+        assert_eq!(
+            Almanac::parse("seeds: 1\n\nseed-to-soil map:\n1 2").err(),
+            Some(AlmanacError::BadMap {
+                category: "seed".to_owned(),
+                error: RangeMapError::InvalidLine("1 2".to_owned())
+            })
+        );
+        assert_eq!(
+            Almanac::parse("seeds: 1 -2").err(),
+            Some(AlmanacError::InvalidSeed("-2".to_owned()))
+        );
+        assert_eq!(
+            Almanac::parse(
+                "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nseed-to-fertilizer map:\n1 2 3"
+            )
+            .err(),
+            Some(AlmanacError::DuplicateCategory("seed".to_owned()))
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use std::{
-    iter::zip,
-    ops::{Mul, Sub},
+    fmt::Debug,
+    ops::{Mul, RangeInclusive, Sub},
+    str::FromStr,
 };
 
+use num::{integer::Roots, Integer};
+
 use crate::TaskCompleter;
 
 fn get_distance_for_given_charge<T>(total_time: T, charging_time: T) -> T
@@ -15,52 +18,113 @@ where
     distance
 }
 
+#[derive(Debug, PartialEq, Clone)]
+struct Race<T> {
+    time: T,
+    record: T,
+}
+
+impl<T> Race<T>
+where
+    T: Integer + Roots + Clone + From<u8>,
+{
+    fn beats_record(&self, charge: &T) -> bool {
+        charge.clone() * (self.time.clone() - charge.clone()) > self.record
+    }
+
+    /// The charging times that beat the record, found from the roots of
+    /// `charge * (time - charge) = record`
+    fn winning_charges(&self) -> Option<RangeInclusive<T>> {
+        let two = T::from(2);
+        let four = T::from(4);
+        let squared = self.time.clone() * self.time.clone();
+        let bound = four * self.record.clone();
+        if squared <= bound {
+            return None;
+        }
+        let root = (squared - bound).sqrt();
+        // The square root is rounded down, so the first winning charge is at most a step or two
+        // above this
+        let mut first = (self.time.clone() - root) / two.clone();
+        let middle = self.time.clone() / two;
+        while first <= middle && !self.beats_record(&first) {
+            first = first + T::one();
+        }
+        if first > middle {
+            return None;
+        }
+        // Charging for `c` and `time - c` travel the same distance
+        let last = self.time.clone() - first.clone();
+        Some(first..=last)
+    }
+
+    fn ways_to_win(&self) -> T {
+        match self.winning_charges() {
+            Some(charges) => charges.end().clone() - charges.start().clone() + T::one(),
+            None => T::zero(),
+        }
+    }
+}
+
+fn parse_line<'a>(line: Option<&'a str>, label: &str) -> std::str::SplitWhitespace<'a> {
+    line.and_then(|x| x.trim().strip_prefix(label))
+        .unwrap_or_else(|| panic!("Expected a line starting with '{}'", label))
+        .split_whitespace()
+}
+
+/// Read the races from the sheet. With `kerning` the numbers on each line are joined into one
+/// race.
+fn parse_races<T>(input: &str, kerning: bool) -> Vec<Race<T>>
+where
+    T: FromStr,
+    T::Err: Debug,
+{
+    let mut lines = input.lines().filter(|x| !x.trim().is_empty());
+    let mut numbers = |label| -> Vec<T> {
+        let parts = parse_line(lines.next(), label);
+        let parts: Vec<String> = if kerning {
+            vec![parts.collect()]
+        } else {
+            parts.map(|x| x.to_owned()).collect()
+        };
+        parts
+            .iter()
+            .map(|x| {
+                x.parse::<T>()
+                    .unwrap_or_else(|e| panic!("Invalid number '{}': {:?}", x, e))
+            })
+            .collect()
+    };
+    let times = numbers("Time:");
+    let records = numbers("Distance:");
+    assert_eq!(
+        times.len(),
+        records.len(),
+        "Every race needs a time and a record"
+    );
+    times
+        .into_iter()
+        .zip(records)
+        .map(|(time, record)| Race { time, record })
+        .collect()
+}
+
 pub struct Task6;
 
 impl TaskCompleter for Task6 {
     fn do_task_1(&self) -> String {
         let contents = include_str!("../input/six/input");
-        let mut lines = contents.lines();
-        let times: &Vec<i32> = &lines.next().unwrap()[12..]
-            .split("   ")
-            .map(|x| x.trim().parse::<i32>().unwrap())
-            .collect();
-        let distances: &Vec<i32> = &lines.next().unwrap()[12..]
-            .split("   ")
-            .map(|x| x.trim().parse::<i32>().unwrap())
-            .collect();
-
-        zip(times, distances)
-            .map(|(time, record_distance)| {
-                (0..*time)
-                    .map(|charging_time| get_distance_for_given_charge(*time, charging_time))
-                    .filter(|distance| distance > record_distance)
-                    .count()
-            })
-            .fold(1, |x, y| x * y)
+        parse_races::<u64>(contents, false)
+            .iter()
+            .map(|race| race.ways_to_win())
+            .product::<u64>()
             .to_string()
     }
 
     fn do_task_2(&self) -> String {
         let contents = include_str!("../input/six/input");
-        let mut lines = contents.lines();
-        let time = &lines.next().unwrap()[12..]
-            .split("   ")
-            .map(|x| x.trim())
-            .fold(String::new(), |x, y| x + y)
-            .parse::<u64>()
-            .unwrap();
-        let record_distance = &lines.next().unwrap()[12..]
-            .split("   ")
-            .map(|x| x.trim())
-            .fold(String::new(), |x, y| x + y)
-            .parse::<u64>()
-            .unwrap();
-
-        (0..*time)
-            .map(|charging_time| get_distance_for_given_charge(*time, charging_time))
-            .filter(|distance| distance > record_distance)
-            .count()
+        parse_races::<u64>(contents, true)[0]
+            .ways_to_win()
             .to_string()
     }
 
@@ -72,3 +136,65 @@ impl TaskCompleter for Task6 {
         Some("21039729".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+
+    use crate::day_06::{get_distance_for_given_charge, parse_races, Race};
+
+    const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200
+";
+
+    #[test]
+    fn example_races() {
+        let races = parse_races::<u64>(EXAMPLE, false);
+        let ways: Vec<u64> = races.iter().map(|x| x.ways_to_win()).collect();
+        assert_eq!(ways, vec![4, 8, 9]);
+        assert_eq!(races[0].winning_charges(), Some(2..=5));
+
+        let kerned = parse_races::<u64>(EXAMPLE, true);
+        assert_eq!(
+            kerned,
+            vec![Race {
+                time: 71530,
+                record: 940200
+            }]
+        );
+        assert_eq!(kerned[0].ways_to_win(), 71503);
+    }
+
+    #[test]
+    fn matches_counting_every_charge() {
+        for time in 0..40u64 {
+            for record in 0..time * time / 4 + 2 {
+                let expected = (0..=time)
+                    .filter(|c| get_distance_for_given_charge(time, *c) > record)
+                    .count() as u64;
+                assert_eq!(Race { time, record }.ways_to_win(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn beyond_u64() {
+        let race = Race::<u128> {
+            time: 10_000_000_000_000_000_000,
+            record: 10_u128.pow(37),
+        };
+        assert_eq!(race.ways_to_win(), 7_745_966_692_414_833_771);
+
+        let time: BigUint = "1000000000000000000000000000000".parse().unwrap();
+        let race = Race {
+            time: time.clone(),
+            record: &time * &time / 4u8 - 1u8,
+        };
+        assert_eq!(race.ways_to_win(), BigUint::from(1u8));
+        let race = Race {
+            time: time.clone(),
+            record: &time * &time / 4u8,
+        };
+        assert_eq!(race.ways_to_win(), BigUint::from(0u8));
+    }
+}
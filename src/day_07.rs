@@ -1,8 +1,10 @@
+use std::collections::BTreeMap;
+
 use crate::TaskCompleter;
 
 pub struct Task7;
 
-#[derive(PartialEq, PartialOrd, Eq, Ord, Debug)]
+#[derive(PartialEq, PartialOrd, Eq, Ord, Debug, Clone, Copy)]
 pub enum HandType {
     HighCard,
     OnePair,
@@ -14,238 +16,175 @@ pub enum HandType {
 }
 
 impl HandType {
-    fn new(hand: &[u32; 5]) -> Self {
-        let mut count = [0; 13];
-        for card in hand {
-            count[*card as usize] += 1;
-        }
-        if count.contains(&5) {
-            return HandType::FiveOfAKind;
-        } else if count.contains(&4) {
-            return HandType::FourOfAKind;
-        } else if count.contains(&3) {
-            if count.contains(&2) {
-                return HandType::FullHouse;
-            } else {
-                return HandType::ThreeOfAKind;
-            }
-        }
-        match count.iter().filter(|x| x == &&2).count() {
-            0 => HandType::HighCard,
-            1 => HandType::OnePair,
-            2 => HandType::TwoPair,
-            _ => panic!("Can't have more than two pairs from 5 cards"),
+    /// Classify a hand from how many times each card appears, ignoring wild cards, and the
+    /// number of wild cards. Wild cards always do best joining the largest group, and anything
+    /// more than five of a kind still counts as five of a kind.
+    fn from_counts(counts: &[usize], wild: usize) -> Self {
+        let mut groups: Vec<usize> = counts.iter().copied().filter(|x| *x > 0).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        let largest = groups.first().copied().unwrap_or(0) + wild;
+        let second = groups.get(1).copied().unwrap_or(0);
+        match (largest, second) {
+            (5.., _) => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, 2..) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2..) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
         }
     }
+}
 
-    fn new_2(hand: &[u32; 5]) -> Self {
-        let mut count = [0; 13];
-        for card in hand {
-            count[*card as usize] += 1;
-        }
-        let joker_count = count[0];
-        let count = &count[1..13];
+/// How a game of Camel Cards is played: the card labels from weakest to strongest, which of
+/// them are wild and how many cards make a hand
+#[derive(Debug, Clone)]
+pub struct Rules {
+    order: Vec<char>,
+    wild: Vec<bool>,
+    hand_size: usize,
+}
 
-        if count.contains(&(5 - joker_count)) {
-            return HandType::FiveOfAKind;
-        } else if count.contains(&(4 - joker_count)) {
-            return HandType::FourOfAKind;
+impl Rules {
+    fn new(order: &str, wild: &str, hand_size: usize) -> Self {
+        let order: Vec<char> = order.chars().collect();
+        for card in wild.chars() {
+            assert!(order.contains(&card), "Wild card {} isn't ranked", card);
         }
-
-        // here can have at most 2 joker, as 3 or more will make four of a kind
-        assert!(joker_count <= 2);
-        if joker_count == 2 {
-            // Can't have any counts of two as that would have returned four of a kind
-            HandType::ThreeOfAKind
-        } else if joker_count == 1 {
-            match count.iter().filter(|x| x == &&2).count() {
-                0 => HandType::OnePair,
-                1 => HandType::ThreeOfAKind,
-                2 => HandType::FullHouse,
-                _ => panic!("Can't have more than two pairs from 5 cards"),
-            }
-        } else {
-            if count.contains(&3) {
-                if count.contains(&2) {
-                    HandType::FullHouse
-                } else {
-                    HandType::ThreeOfAKind
-                }
-            } else {
-                match count.iter().filter(|x| x == &&2).count() {
-                    0 => HandType::HighCard,
-                    1 => HandType::OnePair,
-                    2 => HandType::TwoPair,
-                    _ => panic!("Can't have more than two pairs from 5 cards"),
-                }
-            }
+        Self {
+            wild: order.iter().map(|x| wild.contains(*x)).collect(),
+            order,
+            hand_size,
         }
     }
-}
-
-pub struct Hand {
-    hand: [u32; 5],
-    hand_type: HandType,
-    bid: u32,
-}
 
-impl Eq for Hand {}
+    fn standard() -> Self {
+        Self::new("23456789TJQKA", "", 5)
+    }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.hand_type.cmp(&other.hand_type) {
-            core::cmp::Ordering::Equal => {}
-            ord => return ord,
-        }
-        self.hand.cmp(&other.hand)
+    /// `J` is a joker, wild but weaker than any other card when breaking ties
+    fn jokers() -> Self {
+        Self::new("J23456789TQKA", "J", 5)
     }
-}
 
-impl PartialEq for Hand {
-    fn eq(&self, other: &Self) -> bool {
-        self.hand == other.hand && self.hand_type == other.hand_type
+    fn card_value(&self, card: char) -> u32 {
+        self.order
+            .iter()
+            .position(|x| *x == card)
+            .unwrap_or_else(|| panic!("Invalid card {}", card)) as u32
     }
-}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.hand_type.partial_cmp(&other.hand_type) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
+    fn hand_type(&self, cards: &[u32]) -> HandType {
+        let mut counts = vec![0; self.order.len()];
+        let mut wild = 0;
+        for card in cards {
+            if self.wild[*card as usize] {
+                wild += 1;
+            } else {
+                counts[*card as usize] += 1;
+            }
         }
-        self.hand.partial_cmp(&other.hand)
+        HandType::from_counts(&counts, wild)
     }
-}
 
-impl Hand {
-    fn new(x: &str) -> Self {
-        let mut s = x.split(" ");
-        let hand = s
-            .next()
-            .unwrap()
-            .chars()
-            .map(|x| match x {
-                '2' => 0,
-                '3' => 1,
-                '4' => 2,
-                '5' => 3,
-                '6' => 4,
-                '7' => 5,
-                '8' => 6,
-                '9' => 7,
-                'T' => 8,
-                'J' => 9,
-                'Q' => 10,
-                'K' => 11,
-                'A' => 12,
-                _ => panic!("Invalid Card"),
-            })
-            .collect::<Vec<u32>>()
-            .try_into()
-            .unwrap();
-        let bid = s.next().unwrap().parse::<u32>().unwrap();
-        Self {
-            hand,
-            bid,
-            hand_type: HandType::new(&hand),
+    fn parse_hand(&self, line: &str) -> Hand {
+        let (cards, bid) = line
+            .split_once(' ')
+            .unwrap_or_else(|| panic!("Expected a hand and a bid in '{}'", line));
+        let cards: Vec<u32> = cards.chars().map(|x| self.card_value(x)).collect();
+        assert_eq!(
+            cards.len(),
+            self.hand_size,
+            "Wrong number of cards in '{}'",
+            line
+        );
+        Hand {
+            hand_type: self.hand_type(&cards),
+            cards,
+            bid: bid.trim().parse().unwrap(),
         }
     }
-}
 
-pub struct Hand2 {
-    hand: [u32; 5],
-    hand_type: HandType,
-    bid: u32,
-}
-
-impl Eq for Hand2 {}
+    /// How many of the possible hands, counting every order of cards separately, there are of
+    /// each type
+    fn distribution(&self) -> BTreeMap<HandType, u64> {
+        let mut distribution = BTreeMap::new();
+        let mut counts = vec![0; self.order.len()];
+        self.add_hands(&mut counts, 0, self.hand_size, &mut distribution);
+        distribution
+    }
 
-impl Ord for Hand2 {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.hand_type.cmp(&other.hand_type) {
-            core::cmp::Ordering::Equal => {}
-            ord => return ord,
+    // Choose how many of each card from `card` onwards make up the rest of the hand
+    fn add_hands(
+        &self,
+        counts: &mut Vec<usize>,
+        card: usize,
+        remaining: usize,
+        distribution: &mut BTreeMap<HandType, u64>,
+    ) {
+        if card == counts.len() {
+            if remaining == 0 {
+                let mut unwild = counts.clone();
+                let mut wild = 0;
+                for (card, count) in unwild.iter_mut().enumerate() {
+                    if self.wild[card] {
+                        wild += *count;
+                        *count = 0;
+                    }
+                }
+                // The number of orders the cards can come in
+                let orders = counts
+                    .iter()
+                    .fold(factorial(self.hand_size), |x, c| x / factorial(*c));
+                *distribution
+                    .entry(HandType::from_counts(&unwild, wild))
+                    .or_insert(0) += orders;
+            }
+            return;
+        }
+        for count in 0..=remaining {
+            counts[card] = count;
+            self.add_hands(counts, card + 1, remaining - count, distribution);
         }
-        self.hand.cmp(&other.hand)
+        counts[card] = 0;
     }
-}
 
-impl PartialEq for Hand2 {
-    fn eq(&self, other: &Self) -> bool {
-        self.hand == other.hand && self.hand_type == other.hand_type
+    /// Chance of a uniformly random hand being of the given type
+    fn probability(&self, hand_type: HandType) -> f64 {
+        let total = (self.order.len() as f64).powi(self.hand_size as i32);
+        self.distribution().get(&hand_type).copied().unwrap_or(0) as f64 / total
     }
 }
 
-impl PartialOrd for Hand2 {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        match self.hand_type.partial_cmp(&other.hand_type) {
-            Some(core::cmp::Ordering::Equal) => {}
-            ord => return ord,
-        }
-        self.hand.partial_cmp(&other.hand)
-    }
+fn factorial(n: usize) -> u64 {
+    (1..=n as u64).product()
 }
 
-impl Hand2 {
-    fn new(x: &str) -> Self {
-        let mut s = x.split(" ");
-        let hand = s
-            .next()
-            .unwrap()
-            .chars()
-            .map(|x| match x {
-                'J' => 0,
-                '2' => 1,
-                '3' => 2,
-                '4' => 3,
-                '5' => 4,
-                '6' => 5,
-                '7' => 6,
-                '8' => 7,
-                '9' => 8,
-                'T' => 9,
-                'Q' => 10,
-                'K' => 11,
-                'A' => 12,
-                _ => panic!("Invalid Card"),
-            })
-            .collect::<Vec<u32>>()
-            .try_into()
-            .unwrap();
-        let bid = s.next().unwrap().parse::<u32>().unwrap();
-        Self {
-            hand,
-            bid,
-            hand_type: HandType::new_2(&hand),
-        }
-    }
+#[derive(PartialEq, PartialOrd, Eq, Ord, Debug)]
+pub struct Hand {
+    // Field order matters for the derived ordering: type first, then the cards in turn
+    hand_type: HandType,
+    cards: Vec<u32>,
+    bid: u32,
+}
+
+fn total_winnings(rules: &Rules, input: &str) -> usize {
+    let mut hands: Vec<Hand> = input.lines().map(|x| rules.parse_hand(x)).collect();
+    hands.sort();
+    hands
+        .iter()
+        .enumerate()
+        .map(|(i, hand)| (i + 1) * hand.bid as usize)
+        .sum()
 }
 
 impl TaskCompleter for Task7 {
     fn do_task_1(&self) -> String {
-        let mut c: Vec<Hand> = include_str!("../input/seven/input")
-            .lines()
-            .map(|x| Hand::new(x))
-            .collect();
-        c.sort();
-        let mut sum = 0;
-        for i in 0..c.len() {
-            sum += (i + 1) * c[i].bid as usize;
-        }
-        sum.to_string()
+        total_winnings(&Rules::standard(), include_str!("../input/seven/input")).to_string()
     }
 
     fn do_task_2(&self) -> String {
-        let mut c: Vec<Hand2> = include_str!("../input/seven/input")
-            .lines()
-            .map(|x| Hand2::new(x))
-            .collect();
-        c.sort();
-        let mut sum = 0;
-        for i in 0..c.len() {
-            sum += (i + 1) * c[i].bid as usize;
-        }
-        sum.to_string()
+        total_winnings(&Rules::jokers(), include_str!("../input/seven/input")).to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -259,35 +198,69 @@ impl TaskCompleter for Task7 {
 
 #[cfg(test)]
 mod tests {
-    use crate::day_07::{Hand2, HandType};
+    use crate::day_07::{total_winnings, HandType, Rules};
 
     #[test]
     fn new_2_tests() {
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("AAAAA 0").hand_type);
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("JAAAA 0").hand_type);
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("JJAAA 0").hand_type);
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("JJJAA 0").hand_type);
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("JJJJA 0").hand_type);
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("JJJJJ 0").hand_type);
+        let rules = Rules::jokers();
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("AAAAA 0").hand_type);
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("JAAAA 0").hand_type);
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("JJAAA 0").hand_type);
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("JJJAA 0").hand_type);
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("JJJJA 0").hand_type);
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("JJJJJ 0").hand_type);
 
-        assert_eq!(HandType::FullHouse, Hand2::new("AAJ22 0").hand_type);
-        assert_eq!(HandType::FourOfAKind, Hand2::new("AJJ22 0").hand_type);
-        assert_eq!(HandType::FiveOfAKind, Hand2::new("JJJ22 0").hand_type);
-        assert_eq!(HandType::OnePair, Hand2::new("J2345 0").hand_type);
-        assert_eq!(HandType::ThreeOfAKind, Hand2::new("J2245 0").hand_type);
+        assert_eq!(HandType::FullHouse, rules.parse_hand("AAJ22 0").hand_type);
+        assert_eq!(HandType::FourOfAKind, rules.parse_hand("AJJ22 0").hand_type);
+        assert_eq!(HandType::FiveOfAKind, rules.parse_hand("JJJ22 0").hand_type);
+        assert_eq!(HandType::OnePair, rules.parse_hand("J2345 0").hand_type);
+        assert_eq!(
+            HandType::ThreeOfAKind,
+            rules.parse_hand("J2245 0").hand_type
+        );
     }
 
     #[test]
-    fn run_2_on_example() {
-        let mut c: Vec<Hand2> = include_str!("../input/seven/example")
-            .lines()
-            .map(|x| Hand2::new(x))
-            .collect();
-        c.sort();
-        let mut sum = 0;
-        for i in 0..c.len() {
-            sum += (i + 1) * c[i].bid as usize;
-        }
-        assert_eq!(sum, 5905);
+    fn run_on_example() {
+        let example = include_str!("../input/seven/example");
+        assert_eq!(total_winnings(&Rules::standard(), example), 6440);
+        assert_eq!(total_winnings(&Rules::jokers(), example), 5905);
+    }
+
+    #[test]
+    fn other_hand_sizes_and_wild_cards() {
+        let rules = Rules::new("J23456789TQKA", "J", 7);
+        assert_eq!(
+            HandType::FourOfAKind,
+            rules.parse_hand("JJJ2345 0").hand_type
+        );
+        assert_eq!(
+            HandType::FiveOfAKind,
+            rules.parse_hand("JJJ2245 0").hand_type
+        );
+        assert_eq!(HandType::FullHouse, rules.parse_hand("J223344 0").hand_type);
+
+        let rules = Rules::new("23456789TJQKA", "2J", 3);
+        assert_eq!(HandType::ThreeOfAKind, rules.parse_hand("2JA 0").hand_type);
+        assert_eq!(HandType::ThreeOfAKind, rules.parse_hand("22J 0").hand_type);
+        assert_eq!(HandType::OnePair, rules.parse_hand("2KA 0").hand_type);
+        assert_eq!(HandType::HighCard, rules.parse_hand("3KA 0").hand_type);
+    }
+
+    #[test]
+    fn hand_type_distribution() {
+        let distribution: Vec<u64> = Rules::standard().distribution().into_values().collect();
+        assert_eq!(
+            distribution,
+            vec![154440, 171600, 25740, 17160, 1560, 780, 13]
+        );
+        assert_eq!(distribution.iter().sum::<u64>(), 13u64.pow(5));
+
+        let jokers = Rules::jokers().distribution();
+        assert_eq!(jokers.values().sum::<u64>(), 13u64.pow(5));
+        assert_eq!(jokers[&HandType::HighCard], 12 * 11 * 10 * 9 * 8);
+        assert!(
+            (Rules::standard().probability(HandType::FiveOfAKind) - 13.0 / 371293.0).abs() < 1e-12
+        );
     }
 }
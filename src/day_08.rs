@@ -1,6 +1,11 @@
-use std::{collections::HashMap, iter::zip, thread};
+use std::{collections::HashMap, fmt::Display, iter::zip, thread};
 
-use crate::TaskCompleter;
+use num::integer::{ExtendedGcd, Integer};
+
+use crate::{
+    cycle::{find_cycle, Cycle},
+    TaskCompleter,
+};
 
 pub struct Task8;
 
@@ -92,98 +97,199 @@ pub fn do_task_2_maybe_quicker() -> String {
     handler.join().unwrap()
 }
 
-fn do_task_2_maybe_smarter() -> String {
-    let contents = include_str!("../input/day_08/input");
-    let mut lines = contents.lines();
-    let instructions: Vec<char> = lines.next().unwrap().chars().collect();
-    // Empty line
-    lines.next();
-    let mut mapping = HashMap::<&str, (&str, &str)>::new();
-    for line in lines {
-        mapping.insert(&line[0..3], (&line[7..10], &line[12..15]));
+struct Network<'a> {
+    instructions: Vec<char>,
+    nodes: HashMap<&'a str, (&'a str, &'a str)>,
+}
+
+impl<'a> Network<'a> {
+    fn parse(contents: &'a str) -> Self {
+        let mut lines = contents.lines();
+        let instructions: Vec<char> = lines.next().unwrap().chars().collect();
+        // Empty line
+        lines.next();
+        let mut nodes = HashMap::new();
+        for line in lines {
+            nodes.insert(&line[0..3], (&line[7..10], &line[12..15]));
+        }
+        Self {
+            instructions,
+            nodes,
+        }
     }
 
-    let spaces: Vec<&str> = mapping
-        .iter()
-        .filter_map(|(k, _)| if k.ends_with("A") { Some(*k) } else { None })
-        .collect();
+    // A ghost's state is where it is and which instruction it will follow next
+    fn step(&self, (node, index): &mut (&'a str, usize)) {
+        *node = match self.instructions[*index] {
+            'L' => self.nodes[node].0,
+            'R' => self.nodes[node].1,
+            x => panic!("Invalid character {}", x),
+        };
+        *index = (*index + 1) % self.instructions.len();
+    }
 
-    let first_cycle: Vec<(&str, &str, usize)> = spaces
-        .iter()
-        .map(|x| {
-            let mut count = 0;
-            let mut space = *x;
-            let starting_value = x;
-            while !space.ends_with("Z") {
-                let dir = instructions[count % instructions.len()];
-                space = match dir {
-                    'L' => mapping[space].0,
-                    'R' => mapping[space].1,
-                    _ => panic!("Invalid character {}", dir),
-                };
-                count += 1;
+    /// Follow the instructions from `start` until the walk loops, noting every step that lands
+    /// on an end node
+    fn analyse<F>(&self, start: &'a str, is_end: F) -> GhostWalk
+    where
+        F: Fn(&str) -> bool,
+    {
+        let cycle = find_cycle(&(start, 0), |x| self.step(x));
+        let mut walk = GhostWalk {
+            cycle,
+            prefix_hits: vec![],
+            cycle_hits: vec![],
+        };
+        let mut state = (start, 0);
+        for i in 0..cycle.start + cycle.length {
+            if is_end(state.0) {
+                if i < cycle.start {
+                    walk.prefix_hits.push(i);
+                } else {
+                    walk.cycle_hits.push(i);
+                }
             }
-            (*starting_value, *x, count)
-        })
-        .collect();
+            self.step(&mut state);
+        }
+        walk
+    }
 
-    let all_subsequent_cycles: Vec<(&str, usize, usize, usize)> = first_cycle
-        .iter()
-        .map(|(x, starting_value, initial_cycle_count)| {
-            let mut count = 0;
-            let mut space = *x;
-            while !space.ends_with("Z") {
-                let dir = instructions[count % instructions.len()];
-                space = match dir {
-                    'L' => mapping[space].0,
-                    'R' => mapping[space].1,
-                    _ => panic!("Invalid character {}", dir),
-                };
-                count += 1;
+    /// The first step at which ghosts walking from every start are all on end nodes together
+    fn ghost_steps<F>(&self, starts: &[&'a str], is_end: F) -> Result<u64, GhostWalkError>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let walks: Vec<GhostWalk> = starts.iter().map(|x| self.analyse(x, &is_end)).collect();
+        for (start, walk) in zip(starts, &walks) {
+            if walk.prefix_hits.is_empty() && walk.cycle_hits.is_empty() {
+                return Err(GhostWalkError::NeverEnds(start.to_string()));
             }
-            (
-                *starting_value,
-                *initial_cycle_count,
-                count,
-                *initial_cycle_count,
-            )
-        })
-        .collect();
+        }
+        common_hit(&walks).ok_or(GhostWalkError::NoCommonStep)
+    }
+}
 
-    // Find a number where all initial_cycle_count + count is equal accross all different starting spaces
-    all_subsequent_cycles
-        .iter()
-        .fold(1, |x, y| num::integer::lcm(x, y.1))
-        .to_string()
+/// Where one ghost's walk loops and the steps at which it is on an end node. Hits in the loop
+/// repeat every `cycle.length` steps.
+#[derive(Debug, PartialEq)]
+struct GhostWalk {
+    cycle: Cycle,
+    prefix_hits: Vec<u64>,
+    cycle_hits: Vec<u64>,
 }
 
-impl TaskCompleter for Task8 {
-    fn do_task_1(&self) -> String {
-        let contents = include_str!("../input/day_08/input");
-        let mut lines = contents.lines();
-        let instructions: Vec<char> = lines.next().unwrap().chars().collect();
-        // Empty line
-        lines.next();
-        let mut mapping = HashMap::<&str, (&str, &str)>::new();
-        for line in lines {
-            mapping.insert(&line[0..3], (&line[7..10], &line[12..15]));
+impl GhostWalk {
+    fn is_hit(&self, step: u64) -> bool {
+        if step < self.cycle.start {
+            self.prefix_hits.contains(&step)
+        } else {
+            self.cycle_hits.contains(&self.cycle.equivalent_step(step))
         }
-        let mut space = "AAA";
-        let mut count = 0;
-        while space != "ZZZ" {
-            let dir = instructions[count % instructions.len()];
-            space = match dir {
-                'L' => mapping[space].0,
-                'R' => mapping[space].1,
-                _ => panic!("Invalid character {}", dir),
-            };
-            count += 1;
+    }
+
+    /// Every hit in order, going on forever if there are any in the loop
+    fn hits(&self) -> impl Iterator<Item = u64> + '_ {
+        let looped = (0..)
+            .take_while(|_| !self.cycle_hits.is_empty())
+            .flat_map(move |k| {
+                self.cycle_hits
+                    .iter()
+                    .map(move |x| x + k * self.cycle.length)
+            });
+        self.prefix_hits.iter().copied().chain(looped)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum GhostWalkError {
+    /// The ghost from this start never reaches an end node
+    NeverEnds(String),
+    /// The ghosts are never all on end nodes at once
+    NoCommonStep,
+}
+
+impl Display for GhostWalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GhostWalkError::NeverEnds(x) => write!(f, "Ghost from {} never reaches an end", x),
+            GhostWalkError::NoCommonStep => f.write_str("Ghosts never all reach an end together"),
         }
-        count.to_string()
+    }
+}
+
+/// Solve `x = a (mod m)` and `x = b (mod n)` together, giving the combined residue and modulus,
+/// or `None` if no `x` satisfies both
+fn combine_congruences((a, m): (i128, i128), (b, n): (i128, i128)) -> Option<(i128, i128)> {
+    let ExtendedGcd { gcd, x, .. } = m.extended_gcd(&n);
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+    let lcm = m / gcd * n;
+    // `x * m = gcd (mod n)`, so this many steps of `m` from `a` reach `b`
+    let k = ((b - a) / gcd * x) % (n / gcd);
+    Some(((a + m * k).rem_euclid(lcm), lcm))
+}
+
+/// The first step that is a hit for every walk
+fn common_hit(walks: &[GhostWalk]) -> Option<u64> {
+    // Before every walk has reached its loop, just check the hits of one of them
+    let looping_from = walks.iter().map(|x| x.cycle.start).max()?;
+    if let Some(step) = walks[0]
+        .hits()
+        .take_while(|x| *x < looping_from)
+        .find(|x| walks.iter().all(|walk| walk.is_hit(*x)))
+    {
+        return Some(step);
+    }
+
+    // After that each walk is a choice of congruences, one per hit in its loop
+    let mut congruences = vec![(0, 1)];
+    for walk in walks {
+        let length = walk.cycle.length as i128;
+        congruences = congruences
+            .iter()
+            .flat_map(|c| {
+                walk.cycle_hits
+                    .iter()
+                    .filter_map(move |x| combine_congruences(*c, (*x as i128, length)))
+            })
+            .collect();
+    }
+    congruences
+        .into_iter()
+        .map(|(residue, modulus)| {
+            let from = looping_from as i128;
+            let step = if residue < from {
+                residue + (from - residue + modulus - 1) / modulus * modulus
+            } else {
+                residue
+            };
+            step as u64
+        })
+        .min()
+}
+
+impl TaskCompleter for Task8 {
+    fn do_task_1(&self) -> String {
+        let network = Network::parse(include_str!("../input/day_08/input"));
+        network
+            .ghost_steps(&["AAA"], |x| x == "ZZZ")
+            .unwrap()
+            .to_string()
     }
 
     fn do_task_2(&self) -> String {
-        do_task_2_maybe_smarter()
+        let network = Network::parse(include_str!("../input/day_08/input"));
+        let starts: Vec<&str> = network
+            .nodes
+            .keys()
+            .copied()
+            .filter(|x| x.ends_with('A'))
+            .collect();
+        network
+            .ghost_steps(&starts, |x| x.ends_with('Z'))
+            .unwrap()
+            .to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -199,7 +305,7 @@ impl TaskCompleter for Task8 {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::day_08::str_to_index;
+    use crate::day_08::{combine_congruences, str_to_index, GhostWalkError, Network};
 
     #[test]
     fn str_to_index_test() {
@@ -222,4 +328,73 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn ghost_example() {
+        let network = Network::parse(
+            "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)",
+        );
+        let walk = network.analyse("22A", |x| x.ends_with('Z'));
+        assert_eq!((walk.cycle.start, walk.cycle.length), (1, 6));
+        assert_eq!(walk.cycle_hits, vec![3, 6]);
+        assert_eq!(
+            network.ghost_steps(&["11A", "22A"], |x| x.ends_with('Z')),
+            Ok(6)
+        );
+    }
+
+    #[test]
+    fn walks_with_offsets() {
+        let network = Network::parse(
+            "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+44A = (44Z, 44Z)
+44Z = (44B, 44B)
+44B = (44Z, 44Z)
+55A = (55Z, 55Z)
+55Z = (55B, 55B)
+55B = (55B, 55B)",
+        );
+        let is_end = |x: &str| x.ends_with('Z');
+        // Hits at 2, 4, 6, ... and at 1, 4, 7, ..., where the lcm of the first hits would give 2
+        assert_eq!(network.ghost_steps(&["11A", "22A"], is_end), Ok(4));
+        // Hits at even and odd steps only
+        assert_eq!(
+            network.ghost_steps(&["11A", "44A"], is_end),
+            Err(GhostWalkError::NoCommonStep)
+        );
+        // 55A only hits before it starts looping
+        assert_eq!(network.ghost_steps(&["22A", "55A"], is_end), Ok(1));
+        assert_eq!(
+            network.ghost_steps(&["11A", "55A"], is_end),
+            Err(GhostWalkError::NoCommonStep)
+        );
+        assert_eq!(
+            network.ghost_steps(&["55B"], is_end),
+            Err(GhostWalkError::NeverEnds("55B".to_owned()))
+        );
+    }
+
+    #[test]
+    fn congruences() {
+        assert_eq!(combine_congruences((2, 4), (4, 6)), Some((10, 12)));
+        assert_eq!(combine_congruences((1, 4), (2, 6)), None);
+        assert_eq!(combine_congruences((3, 5), (1, 7)), Some((8, 35)));
+    }
 }
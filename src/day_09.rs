@@ -1,69 +1,33 @@
-use std::{ops::Sub};
-
-use crate::TaskCompleter;
+use crate::{sequence::Polynomial, TaskCompleter};
 
 pub struct Task9;
 
-fn reduce_line<T>(i: &Vec<T>) -> Vec<T>
-where
-    T: Sub<Output = T> + Clone + Copy,
-{
-    let mut value = i[0].clone();
-    i[1..]
-        .iter()
+fn parse_sequences(contents: &str) -> Vec<Polynomial<i128>> {
+    contents
+        .lines()
         .map(|x| {
-            let v = value;
-            value = *x;
-            *x - v
+            let values: Vec<i128> = x.split(" ").map(|x| x.parse::<i128>().unwrap()).collect();
+            Polynomial::fit(&values).unwrap_or_else(|e| panic!("{}: {}", e, x))
         })
         .collect()
 }
 
 impl TaskCompleter for Task9 {
-
     fn do_task_1(&self) -> String {
         let contents = include_str!("../input/day_09/input");
-        let lines = contents.lines();
-
-        lines
-            .map(|x| {
-                let mut i: Vec<i32> = x.split(" ").map(|x| x.parse::<i32>().unwrap()).collect();
-                let mut last_items = vec![];
-                while !i.iter().all(|x| x == &0) {
-                    last_items.push(*i.last().unwrap());
-                    i = reduce_line(&i);
-                }
-                last_items.reverse();
-                let mut value = 0;
-                for i in last_items {
-                    value = i + value;
-                }
-                value
-            })
-            .sum::<i32>()
+        parse_sequences(contents)
+            .iter()
+            .map(|x| x.ahead(1))
+            .sum::<i128>()
             .to_string()
     }
 
     fn do_task_2(&self) -> String {
         let contents = include_str!("../input/day_09/input");
-        let lines = contents.lines();
-
-        lines
-            .map(|x| {
-                let mut i: Vec<i32> = x.split(" ").map(|x| x.parse::<i32>().unwrap()).collect();
-                let mut last_items = vec![];
-                while !i.iter().all(|x| x == &0) {
-                    last_items.push(*i.first().unwrap());
-                    i = reduce_line(&i);
-                }
-                last_items.reverse();
-                let mut value = 0;
-                for i in last_items {
-                    value = i - value;
-                }
-                value
-            })
-            .sum::<i32>()
+        parse_sequences(contents)
+            .iter()
+            .map(|x| x.behind(1))
+            .sum::<i128>()
             .to_string()
     }
 
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, iter, ops::Index};
+use std::{collections::HashMap, ops::Index};
 
 use crate::TaskCompleter;
 
@@ -14,7 +14,7 @@ pub enum Tile {
     Animal,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum Direction {
     North,
     South,
@@ -22,14 +22,6 @@ enum Direction {
     West,
 }
 
-#[derive(PartialEq, Debug, Clone, Copy)]
-enum FloodFillRes {
-    NotFilled,
-    Outside,
-    Inside,
-    Path,
-}
-
 impl Direction {
     fn next(&self, (x, y): (i64, i64)) -> (i64, i64) {
         match self {
@@ -39,83 +31,70 @@ impl Direction {
             Direction::West => (x - 1, y),
         }
     }
+
+    fn opposite(&self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
 }
 
 impl Tile {
-    fn get_next(&self, from: Direction) -> Option<(Direction, Vec<(i64, i64)>, Vec<(i64, i64)>)> {
-        match from {
-            Direction::North => match self {
-                Tile::VerticalPipe => Some((
-                    Direction::North,
-                    vec![(-1, -1), (-1, 0), (-1, 1)],
-                    vec![(1, -1), (1, 0), (1, 1)],
-                )),
-                Tile::StoW => Some((
-                    Direction::West,
-                    vec![(-1, -1)],
-                    vec![(-1, 1), (0, 1), (1, 1), (1, 0), (1, -1)],
-                )),
-                Tile::StoE => Some((
-                    Direction::East,
-                    vec![(-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)],
-                    vec![(1, -1)],
-                )),
-                _ => None,
-            },
-            Direction::South => match self {
-                Tile::VerticalPipe => Some((
-                    Direction::South,
-                    vec![(1, -1), (1, 0), (1, 1)],
-                    vec![(-1, -1), (-1, 0), (-1, 1)],
-                )),
-                Tile::NtoW => Some((
-                    Direction::West,
-                    vec![(1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)],
-                    vec![(-1, 1)],
-                )),
-                Tile::NtoE => Some((
-                    Direction::East,
-                    vec![(1, 1)],
-                    vec![(-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)],
-                )),
-                _ => None,
-            },
-            Direction::East => match self {
-                Tile::HorizontalPipe => Some((
-                    Direction::East,
-                    vec![(-1, 1), (0, 1), (-1, 1)],
-                    vec![(-1, -1), (0, -1), (1, -1)],
-                )),
-                Tile::NtoW => Some((
-                    Direction::North,
-                    vec![(-1, 1)],
-                    vec![(-1, -1), (0, -1), (1, -1), (1, 0), (1, 1)],
-                )),
-                Tile::StoW => Some((
-                    Direction::South,
-                    vec![(-1, 1), (0, 1), (1, 1), (1, 0), (1, -1)],
-                    vec![(-1, -1)],
-                )),
-                _ => None,
-            },
-            Direction::West => match self {
-                Tile::HorizontalPipe => Some((
-                    Direction::West,
-                    vec![(-1, -1), (0, -1), (1, -1)],
-                    vec![(-1, 1), (0, 1), (1, 1)],
-                )),
-                Tile::NtoE => Some((
-                    Direction::North,
-                    vec![(1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)],
-                    vec![(1, 1)],
-                )),
-                Tile::StoE => Some((
-                    Direction::South,
-                    vec![(1, -1)],
-                    vec![(-1, -1), (-1, 0), (-1, 1), (0, 1), (1, 1)],
-                )),
-                _ => None,
-            },
+    /// The two directions a pipe leads in
+    fn connections(&self) -> Option<[Direction; 2]> {
+        match self {
+            Tile::VerticalPipe => Some([Direction::North, Direction::South]),
+            Tile::HorizontalPipe => Some([Direction::East, Direction::West]),
+            Tile::NtoE => Some([Direction::North, Direction::East]),
+            Tile::NtoW => Some([Direction::North, Direction::West]),
+            Tile::StoW => Some([Direction::South, Direction::West]),
+            Tile::StoE => Some([Direction::South, Direction::East]),
+            Tile::Ground | Tile::Animal => None,
+        }
+    }
+
+    fn from_connections(a: Direction, b: Direction) -> Self {
+        [
+            Tile::VerticalPipe,
+            Tile::HorizontalPipe,
+            Tile::NtoE,
+            Tile::NtoW,
+            Tile::StoW,
+            Tile::StoE,
+        ]
+        .into_iter()
+        .find(|x| {
+            let c = x.connections().unwrap();
+            c == [a, b] || c == [b, a]
+        })
+        .unwrap_or_else(|| panic!("No pipe connects {:?} and {:?}", a, b))
+    }
+
+    /// Coming into this tile travelling in direction `from`, the direction it sends you on
+    fn get_next(&self, from: Direction) -> Option<Direction> {
+        let [a, b] = self.connections()?;
+        if a == from.opposite() {
+            Some(b)
+        } else if b == from.opposite() {
+            Some(a)
+        } else {
+            None
+        }
+    }
+
+    fn box_char(&self) -> char {
+        match self {
+            Tile::VerticalPipe => '│',
+            Tile::HorizontalPipe => '─',
+            Tile::NtoE => '└',
+            Tile::NtoW => '┘',
+            Tile::StoW => '┐',
+            Tile::StoE => '┌',
+            Tile::Ground => '.',
+            Tile::Animal => 'S',
         }
     }
 }
@@ -182,128 +161,125 @@ impl Grid {
         }
     }
 
-    fn get_loop(&self) -> Option<(Vec<(i64, i64)>, HashSet<(i64, i64)>, HashSet<(i64, i64)>)> {
-        let mut res = None;
+    /// Find the loop by setting off from the animal in each direction until one leads back
+    fn get_loop(&self) -> Option<PipeLoop> {
         for direction in [
             Direction::East,
             Direction::North,
             Direction::South,
             Direction::West,
         ] {
-            let mut left_side = HashSet::new();
-            let mut right_side = HashSet::new();
-            let mut v = vec![];
+            let mut tiles = vec![self.animal_position];
             let mut pos = direction.next(self.animal_position);
             let mut dir = direction;
-            // println!("Going {:?}", dir);
             while pos != self.animal_position {
-                v.push(pos);
-                if let Some(tile) = self.index_checked(pos.0, pos.1) {
-                    if let Some((d, left, right)) = tile.get_next(dir) {
-                        left_side.extend(left.iter().map(|(x, y)| (pos.0 + x, pos.1 + y)));
-                        right_side.extend(right.iter().map(|(x, y)| (pos.0 + x, pos.1 + y)));
-
-                        dir = d;
-                        pos = dir.next(pos);
-                    } else {
-                        // This loop ends here
-                        //println!("Loop ends due to tile {:?} not accepting direction {:?}", tile, dir);
-                        break;
-                    }
-                } else {
+                let Some(d) = self
+                    .index_checked(pos.0, pos.1)
+                    .and_then(|tile| tile.get_next(dir))
+                else {
                     // This loop ends here
-                    // println!("Loop ends due to tile index {:?} being out of bounds", pos);
                     break;
-                }
+                };
+                tiles.push(pos);
+                dir = d;
+                pos = dir.next(pos);
             }
             if pos == self.animal_position {
-                // Found loop
-                res = Some((v, left_side, right_side));
-                break;
+                return Some(PipeLoop {
+                    tiles,
+                    start_tile: Tile::from_connections(direction, dir.opposite()),
+                });
             }
         }
-        res
+        None
     }
 }
 
-fn do_flood_fill(grid: &mut Vec<Vec<FloodFillRes>>, centre: (i64, i64), to: FloodFillRes) {
-    let offsets = [
-        (0, 1),
-        (0, -1),
-        (1, -1),
-        (1, 0),
-        (1, 1),
-        (-1, -1),
-        (-1, 0),
-        (-1, -1),
-    ];
-    let mut queue = vec![centre];
-
-    while let Some((i, j)) = queue.pop() {
-        if grid[j as usize][i as usize] == FloodFillRes::NotFilled {
-            grid[j as usize][i as usize] = to;
-            queue.append(
-                &mut offsets
-                    .iter()
-                    .map(|(x, y)| (x + i, y + j))
-                    .filter(|(x, y)| in_bounds((*x, *y), grid[0].len() as i64, grid.len() as i64))
-                    .collect::<Vec<(i64, i64)>>(),
-            );
+/// The closed loop of pipe running through the animal's tile
+struct PipeLoop {
+    /// Every tile of the loop in order, starting from the animal's
+    tiles: Vec<(i64, i64)>,
+    /// The pipe hidden under the animal
+    start_tile: Tile,
+}
+
+impl PipeLoop {
+    fn tile(&self, grid: &Grid, pos: (i64, i64)) -> Tile {
+        if pos == grid.animal_position {
+            self.start_tile
+        } else {
+            grid[pos]
         }
     }
-}
 
-fn print_line(g: &Vec<(i64, i64)>, width: i64, height: i64) -> String {
-    let mut v = Vec::from_iter(
-        iter::repeat(Vec::from_iter(iter::repeat(" ").take(width as usize))).take(height as usize),
-    );
-    for (x, y) in g {
-        v[*y as usize][*x as usize] = "x";
+    /// The corners of the loop in order, as the polygon it traces out through tile centres
+    fn vertices(&self, grid: &Grid) -> Vec<(i64, i64)> {
+        self.tiles
+            .iter()
+            .copied()
+            .filter(|x| {
+                !matches!(
+                    self.tile(grid, *x),
+                    Tile::VerticalPipe | Tile::HorizontalPipe
+                )
+            })
+            .collect()
     }
-    v.reverse();
-    v.iter()
-        .map(|x| x.join(""))
-        .collect::<Vec<String>>()
-        .join("\n")
-}
 
-fn print_grid(g: &Vec<Vec<FloodFillRes>>, print: FloodFillRes) -> String {
-    let mut s = g
-        .iter()
-        .map(|x| {
-            x.iter()
-                .map(|y| {
-                    if *y == print {
-                        "X".to_owned()
-                    } else {
-                        " ".to_owned()
-                    }
-                })
-                .collect::<Vec<String>>()
-                .join("")
-        })
-        .collect::<Vec<String>>();
-    s.reverse();
-    s.join("\n")
-}
+    /// The number of tiles inside the loop, from the shoelace formula for the area of the
+    /// polygon and Pick's theorem, `area = inside + boundary / 2 - 1`
+    fn enclosed(&self, grid: &Grid) -> i64 {
+        let vertices = self.vertices(grid);
+        let twice_area: i64 = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+            .sum();
+        (twice_area.abs() - self.tiles.len() as i64) / 2 + 1
+    }
 
-fn in_bounds(pos: (i64, i64), width: i64, height: i64) -> bool {
-    pos.0 >= 0 && pos.0 < width && pos.1 >= 0 && pos.1 < height
+    /// Draw the loop with box drawing characters, marking other tiles `I` if they're inside
+    /// the loop and `O` if they're outside
+    fn render(&self, grid: &Grid) -> String {
+        let on_loop: HashMap<(i64, i64), Tile> = self
+            .tiles
+            .iter()
+            .map(|x| (*x, self.tile(grid, *x)))
+            .collect();
+        let mut rows = vec![];
+        for y in (0..grid.grid.len() as i64).rev() {
+            // Scanning along the row, every pipe leading north crosses the loop's boundary
+            let mut inside = false;
+            let mut row = String::new();
+            for x in 0..grid.grid[0].len() as i64 {
+                match on_loop.get(&(x, y)) {
+                    Some(tile) => {
+                        if tile.connections().unwrap().contains(&Direction::North) {
+                            inside = !inside;
+                        }
+                        row.push(tile.box_char());
+                    }
+                    None => row.push(if inside { 'I' } else { 'O' }),
+                }
+            }
+            rows.push(row);
+        }
+        rows.join("\n")
+    }
 }
 
 pub struct Task10;
 
 impl TaskCompleter for Task10 {
-
     fn do_task_1(&self) -> String {
         let contents = include_str!("../input/day_10/input");
         let grid = Grid::new(contents);
         assert_eq!(grid[grid.animal_position], Tile::Animal);
 
-        let l = grid.get_loop().unwrap().0.len();
+        let l = grid.get_loop().unwrap().tiles.len();
 
         // Answer is half way around the loop
-        format!("{:?}", l / 2 + 1)
+        format!("{:?}", l / 2)
     }
 
     fn do_task_2(&self) -> String {
@@ -311,85 +287,7 @@ impl TaskCompleter for Task10 {
         let grid = Grid::new(contents);
         assert_eq!(grid[grid.animal_position], Tile::Animal);
 
-        let (path, left_side, right_side) = grid.get_loop().unwrap();
-        let mut flood_fill: Vec<Vec<FloodFillRes>> = grid
-            .grid
-            .iter()
-            .map(|x| {
-                x.iter()
-                    .map(|_y| FloodFillRes::NotFilled)
-                    .collect::<Vec<FloodFillRes>>()
-            })
-            .collect();
-        flood_fill[grid.animal_position.1 as usize][grid.animal_position.0 as usize] =
-            FloodFillRes::Path;
-        for (x, y) in &path {
-            flood_fill[*y as usize][*x as usize] = FloodFillRes::Path;
-        }
-
-        for y in 0..flood_fill.len() {
-            if flood_fill[y][0] != FloodFillRes::Path {
-                do_flood_fill(&mut flood_fill, (0 as i64, y as i64), FloodFillRes::Outside);
-            }
-            let l = flood_fill[0].len() - 1;
-            if flood_fill[y][l] != FloodFillRes::Path {
-                do_flood_fill(&mut flood_fill, (l as i64, y as i64), FloodFillRes::Outside);
-            }
-        }
-
-        for x in 0..flood_fill[0].len() {
-            if flood_fill[0][x] != FloodFillRes::Path {
-                do_flood_fill(&mut flood_fill, (x as i64, 0), FloodFillRes::Outside);
-            }
-            let l = flood_fill.len() - 1;
-            if flood_fill[l][x] != FloodFillRes::Path {
-                do_flood_fill(&mut flood_fill, (x as i64, l as i64), FloodFillRes::Outside);
-            }
-        }
-
-        let mut flood_fill_left = flood_fill;
-        let mut flood_fill_right = flood_fill_left.clone();
-        let mut use_left_side = true;
-
-        for pos in left_side
-            .iter()
-            .filter(|x| in_bounds(**x, grid.grid[0].len() as i64, grid.grid.len() as i64))
-        {
-            if flood_fill_left[pos.1 as usize][pos.0 as usize] == FloodFillRes::Outside {
-                use_left_side = false;
-                break;
-            } else if flood_fill_left[pos.1 as usize][pos.0 as usize] == FloodFillRes::NotFilled {
-                do_flood_fill(&mut flood_fill_left, *pos, FloodFillRes::Inside);
-            }
-        }
-        if use_left_side {
-            flood_fill_left
-                .iter()
-                .flatten()
-                .filter(|x| **x == FloodFillRes::Inside)
-                .count()
-                .to_string()
-        } else {
-            for pos in right_side
-                .iter()
-                .filter(|x| in_bounds(**x, grid.grid[0].len() as i64, grid.grid.len() as i64))
-            {
-                if flood_fill_right[pos.1 as usize][pos.0 as usize] == FloodFillRes::Outside {
-                    // println!("{}", print_grid(&flood_fill_right, FloodFillRes::Outside));
-                    panic!["Both sides touch outside, seen at pos: {:?}", pos];
-                } else if flood_fill_right[pos.1 as usize][pos.0 as usize]
-                    == FloodFillRes::NotFilled
-                {
-                    do_flood_fill(&mut flood_fill_right, *pos, FloodFillRes::Inside);
-                }
-            }
-            flood_fill_right
-                .iter()
-                .flatten()
-                .filter(|x| **x == FloodFillRes::Inside)
-                .count()
-                .to_string()
-        }
+        grid.get_loop().unwrap().enclosed(&grid).to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -400,3 +298,51 @@ impl TaskCompleter for Task10 {
         Some("567".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_10::{Grid, Tile};
+
+    #[test]
+    fn infers_start_tile() {
+        let grid = Grid::new("7-F7-\n.FJ|7\nSJLL7\n|F--J\nLJ.LJ");
+        let pipe_loop = grid.get_loop().unwrap();
+        assert_eq!(pipe_loop.start_tile, Tile::StoE);
+        assert_eq!(pipe_loop.tiles.len() / 2, 8);
+        assert_eq!(pipe_loop.tiles[0], grid.animal_position);
+        assert_eq!(pipe_loop.vertices(&grid)[0], grid.animal_position);
+    }
+
+    #[test]
+    fn enclosed_tiles() {
+        for (input, expected) in [
+            (include_str!("../input/day_10/example"), 10),
+            (include_str!("../input/day_10/example2"), 4),
+            (include_str!("../input/day_10/example3"), 4),
+            (include_str!("../input/day_10/example4"), 8),
+        ] {
+            let grid = Grid::new(input);
+            let pipe_loop = grid.get_loop().unwrap();
+            assert_eq!(pipe_loop.enclosed(&grid), expected);
+            let render = pipe_loop.render(&grid);
+            assert_eq!(render.matches('I').count(), expected as usize);
+        }
+    }
+
+    #[test]
+    fn render_loop() {
+        let grid = Grid::new(include_str!("../input/day_10/example3"));
+        let render = grid.get_loop().unwrap().render(&grid);
+        let expected = "\
+OOOOOOOOOO
+O┌──────┐O
+O│┌────┐│O
+O││OOOO││O
+O││OOOO││O
+O│└─┐┌─┘│O
+O│II││II│O
+O└──┘└──┘O
+OOOOOOOOOO";
+        assert_eq!(render, expected);
+    }
+}
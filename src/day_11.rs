@@ -4,9 +4,15 @@ use crate::TaskCompleter;
 
 pub struct Task11;
 
-impl TaskCompleter for Task11 {
-    fn do_task_1(&self) -> String {
-        let contents = include_str!("../input/day_11/input");
+struct Universe {
+    // Galaxies as (column, row), numbered in reading order
+    galaxies: Vec<(usize, usize)>,
+    empty_rows: Vec<bool>,
+    empty_columns: Vec<bool>,
+}
+
+impl Universe {
+    fn parse(contents: &str) -> Self {
         let lines = contents.lines().collect::<Vec<&str>>();
 
         let mut empty_rows = vec![true; lines.len()];
@@ -26,69 +32,102 @@ impl TaskCompleter for Task11 {
                 }
             }
         }
+        Self {
+            galaxies,
+            empty_rows,
+            empty_columns,
+        }
+    }
 
-        let galaxies: Vec<(usize, usize)> = galaxies
-            .iter()
-            .map(|(x, y)| {
-                (
-                    x + empty_columns[0..*x].iter().filter(|x| **x).count(),
-                    y + empty_rows[0..*y].iter().filter(|x| **x).count(),
-                )
-            })
-            .collect();
-
-        zip(0.., &galaxies)
-            .map(|(i, (x1, y1))| {
-                galaxies[i + 1..]
-                    .iter()
-                    .map(|(x2, y2)| x1.abs_diff(*x2) + y1.abs_diff(*y2))
-                    .sum::<usize>()
-            })
-            .sum::<usize>()
-            .to_string()
+    /// Grow every empty column to `x_factor` columns and every empty row to `y_factor` rows
+    fn expand(&self, x_factor: usize, y_factor: usize) -> ExpandedUniverse {
+        // Where each original column or row ends up once the empty ones before it have grown
+        let positions = |empty: &[bool], factor: usize| -> Vec<usize> {
+            empty
+                .iter()
+                .scan(0, |next, e| {
+                    let position = *next;
+                    *next += if *e { factor } else { 1 };
+                    Some(position)
+                })
+                .collect()
+        };
+        let columns = positions(&self.empty_columns, x_factor);
+        let rows = positions(&self.empty_rows, y_factor);
+        ExpandedUniverse {
+            galaxies: self
+                .galaxies
+                .iter()
+                .map(|(x, y)| (columns[*x], rows[*y]))
+                .collect(),
+        }
     }
+}
 
-    fn do_task_2(&self) -> String {
-        const EXPANSION_FACTOR: usize = 999999;
-        let contents = include_str!("../input/day_11/input");
-        let lines = contents.lines().collect::<Vec<&str>>();
+struct ExpandedUniverse {
+    galaxies: Vec<(usize, usize)>,
+}
 
-        let mut empty_rows = vec![true; lines.len()];
-        let mut empty_columns = vec![true; lines[0].len()];
-        let mut galaxies = vec![];
+impl ExpandedUniverse {
+    fn distance(&self, a: usize, b: usize) -> usize {
+        let ((x1, y1), (x2, y2)) = (self.galaxies[a], self.galaxies[b]);
+        x1.abs_diff(x2) + y1.abs_diff(y2)
+    }
 
-        for (j, line) in zip(0.., contents.lines()) {
-            for (i, char) in zip(0.., line.chars()) {
-                match char {
-                    '#' => {
-                        empty_rows[j] = false;
-                        empty_columns[i] = false;
-                        galaxies.push((i, j));
-                    }
-                    '.' => (),
-                    _ => panic!("Invalid character"),
-                }
+    /// Sum of the distances between every pair of galaxies. Each axis is done separately: once
+    /// sorted, a value is that far beyond every value before it.
+    fn sum_of_distances(&self) -> usize {
+        let axis_sum = |mut values: Vec<usize>| -> usize {
+            values.sort_unstable();
+            let mut before = 0;
+            zip(0.., values)
+                .map(|(i, x)| {
+                    let total = x * i - before;
+                    before += x;
+                    total
+                })
+                .sum()
+        };
+        axis_sum(self.galaxies.iter().map(|x| x.0).collect())
+            + axis_sum(self.galaxies.iter().map(|x| x.1).collect())
+    }
+
+    /// The two galaxies furthest apart and their distance. The distance is the largest spread
+    /// of either `x + y` or `x - y` across the galaxies.
+    fn farthest_pair(&self) -> Option<(usize, usize, usize)> {
+        let mut best = None;
+        for key in [
+            |(x, y): (usize, usize)| x as i64 + y as i64,
+            |(x, y): (usize, usize)| x as i64 - y as i64,
+        ] {
+            let keyed = || zip(0.., &self.galaxies).map(|(i, g)| (key(*g), i));
+            let (Some((low, a)), Some((high, b))) = (keyed().min(), keyed().max()) else {
+                return None;
+            };
+            let spread = (high - low) as usize;
+            if best.is_none_or(|(_, _, d)| spread > d) {
+                best = Some((a, b, spread));
             }
         }
+        best
+    }
+}
+
+impl TaskCompleter for Task11 {
+    fn do_task_1(&self) -> String {
+        let contents = include_str!("../input/day_11/input");
+        Universe::parse(contents)
+            .expand(2, 2)
+            .sum_of_distances()
+            .to_string()
+    }
 
-        let galaxies: Vec<(usize, usize)> = galaxies
-            .iter()
-            .map(|(x, y)| {
-                (
-                    x + (EXPANSION_FACTOR * empty_columns[0..*x].iter().filter(|x| **x).count()),
-                    y + (EXPANSION_FACTOR * empty_rows[0..*y].iter().filter(|x| **x).count()),
-                )
-            })
-            .collect();
-
-        zip(0.., &galaxies)
-            .map(|(i, (x1, y1))| {
-                galaxies[i + 1..]
-                    .iter()
-                    .map(|(x2, y2)| x1.abs_diff(*x2) + y1.abs_diff(*y2))
-                    .sum::<usize>()
-            })
-            .sum::<usize>()
+    fn do_task_2(&self) -> String {
+        const EXPANSION_FACTOR: usize = 1_000_000;
+        let contents = include_str!("../input/day_11/input");
+        Universe::parse(contents)
+            .expand(EXPANSION_FACTOR, EXPANSION_FACTOR)
+            .sum_of_distances()
             .to_string()
     }
 
@@ -100,3 +139,52 @@ impl TaskCompleter for Task11 {
         Some("632003913611".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_11::Universe;
+
+    const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....
+";
+
+    #[test]
+    fn example_distances() {
+        let universe = Universe::parse(EXAMPLE);
+        let expanded = universe.expand(2, 2);
+        assert_eq!(expanded.distance(4, 8), 9);
+        assert_eq!(expanded.distance(0, 6), 15);
+        assert_eq!(expanded.distance(2, 5), 17);
+        assert_eq!(expanded.distance(7, 8), 5);
+        assert_eq!(expanded.sum_of_distances(), 374);
+        assert_eq!(universe.expand(10, 10).sum_of_distances(), 1030);
+        assert_eq!(universe.expand(100, 100).sum_of_distances(), 8410);
+        // Only growing columns leaves the rows as they were
+        assert_eq!(universe.expand(2, 1).galaxies[8], (5, 9));
+    }
+
+    #[test]
+    fn matches_every_pair() {
+        let universe = Universe::parse(EXAMPLE);
+        for (x_factor, y_factor) in [(1, 1), (2, 7), (30, 3)] {
+            let expanded = universe.expand(x_factor, y_factor);
+            let n = expanded.galaxies.len();
+            let pairs = (0..n).flat_map(|a| (a + 1..n).map(move |b| (a, b)));
+            let total: usize = pairs.clone().map(|(a, b)| expanded.distance(a, b)).sum();
+            assert_eq!(expanded.sum_of_distances(), total);
+
+            let farthest = pairs.map(|(a, b)| expanded.distance(a, b)).max();
+            let (a, b, d) = expanded.farthest_pair().unwrap();
+            assert_eq!(Some(d), farthest);
+            assert_eq!(expanded.distance(a, b), d);
+        }
+    }
+}
@@ -1,10 +1,6 @@
 use crate::TaskCompleter;
-use rayon::prelude::*;
-use std::{
-    collections::HashMap,
-    iter::{self, zip},
-    time::{Duration, Instant},
-};
+use num::Num;
+use std::iter;
 
 fn verify_sequence(sequence: &Vec<char>, verify: &Vec<u32>) -> bool {
     let mut verify_sequence = None;
@@ -99,182 +95,198 @@ fn get_combinations_brute_force_blown_up(input: &&str) -> u32 {
     get_combi_bf(&mut sequence, &verify, first_question_mark)
 }
 
-fn get_combinations_verify_wise_sub<'a>(
-    cache: &mut HashMap<(&'a [u32], usize), u64>,
-    sequence: &Vec<char>,
-    verify: &'a [u32],
-    from_index: usize,
-) -> u64 {
-    // Get verify, go through all ? and try and fit a sequence of that length
-    // call this function again and again
-    if verify.len() == 0 {
-        // check everything after from_index is '.' or '?'
-        if sequence[from_index..].iter().all(|x| x != &'#') {
-            1
-        } else {
-            0
+/// A row of springs, some of unknown condition, and the sizes of the groups of damaged springs
+/// along it
+struct SpringRow {
+    springs: Vec<char>,
+    groups: Vec<usize>,
+    // How many springs from each position on could be damaged, up to the next operational one
+    runs: Vec<usize>,
+}
+
+impl SpringRow {
+    fn new(springs: Vec<char>, groups: Vec<usize>) -> Self {
+        let mut runs = vec![0; springs.len() + 1];
+        for i in (0..springs.len()).rev() {
+            runs[i] = match springs[i] {
+                '.' => 0,
+                '#' | '?' => runs[i + 1] + 1,
+                x => panic!("Unknown character {}", x),
+            };
         }
-    } else {
-        let key = (verify, from_index);
-        if let Some(res) = cache.get(&key) {
-            *res
-        } else {
-            let number_of_springs = verify[0];
-            let next_hash =
-                get_next_symbol('#', from_index, sequence).unwrap_or(sequence.len() - 2) + 2;
-            let res = (from_index..next_hash)
-                .map(|i| {
-                    if sequence[i - 1] != '#'
-                        && (i + number_of_springs as usize) < sequence.len()
-                        && sequence[i..(i + number_of_springs as usize)]
-                            .iter()
-                            .all(|x| x != &'.')
-                        && sequence[i + number_of_springs as usize] != '#'
-                    {
-                        get_combinations_verify_wise_sub(
-                            cache,
-                            sequence,
-                            &verify[1..],
-                            i + number_of_springs as usize + 1,
-                        )
-                    } else {
-                        0
-                    }
-                })
-                .sum::<u64>();
-            cache.insert(key, res);
-            res
+        Self {
+            springs,
+            groups,
+            runs,
         }
     }
-}
 
-fn get_combinations_verify_wise_sub_with_output(
-    sequence: &Vec<char>,
-    verify: &[u32],
-    from_index: usize,
-) -> Vec<Vec<char>> {
-    // Get verify, go through all ? and try and fit a sequence of that length
-    // call this function again and again
-    if verify.len() == 0 {
-        // check everything after from_index is '.' or '?'
-        if sequence[from_index..]
-            .iter()
-            .all(|x| x == &'?' || x == &'.')
-        {
-            vec![sequence
-                .iter()
-                .map(|x| if *x == '?' { '.' } else { *x })
-                .collect::<Vec<char>>()]
-        } else {
-            vec![]
+    fn parse(line: &str) -> Self {
+        let (springs, groups) = line
+            .split_once(' ')
+            .unwrap_or_else(|| panic!("Expected springs and groups in '{}'", line));
+        Self::new(
+            springs.chars().collect(),
+            groups
+                .split(",")
+                .map(|c| c.parse::<usize>().unwrap())
+                .collect(),
+        )
+    }
+
+    /// Repeat the row `factor` times, with an unknown spring between each copy
+    fn unfold(&self, factor: usize) -> Self {
+        Self::new(
+            vec![self.springs.clone(); factor].join(&'?'),
+            self.groups.repeat(factor),
+        )
+    }
+
+    // Where to carry on from after putting group `j` at position `i`, if it fits there
+    fn group_end(&self, i: usize, j: usize) -> Option<usize> {
+        let len = *self.groups.get(j)?;
+        if self.runs[i] < len {
+            return None;
         }
-    } else {
-        let number_of_springs = verify[0];
-        let next_hash =
-            get_next_symbol('#', from_index, sequence).unwrap_or(sequence.len() - 2) + 2;
-        (from_index..next_hash)
-            .map(|i| {
-                if sequence[i - 1] != '#'
-                    && (i + number_of_springs as usize) < sequence.len()
-                    && sequence[i..(i + number_of_springs as usize)]
-                        .iter()
-                        .all(|x| x != &'.')
-                {
-                    // Possible location
-                    match sequence[i + number_of_springs as usize] {
-                        '?' | '.' => {
-                            let s = get_combinations_verify_wise_sub_with_output(
-                                sequence,
-                                &verify[1..],
-                                i + number_of_springs as usize + 1,
-                            );
-                            let s = s
-                                .into_iter()
-                                .map(|mut x| {
-                                    for j in i..(i + number_of_springs as usize) {
-                                        x[j] = '#';
-                                    }
-                                    x
-                                })
-                                .collect::<Vec<Vec<char>>>();
-                            s
-                        } // Can use this
-                        '#' => vec![], // Sequence would be too long
-                        _ => panic!("Invalid character"),
-                    }
-                } else {
-                    vec![]
+        match self.springs.get(i + len) {
+            None => Some(i + len),
+            Some('#') => None,
+            Some(_) => Some(i + len + 1),
+        }
+    }
+
+    /// `ways[i][j]` is the number of arrangements of the springs from `i` onwards using the
+    /// groups from `j` onwards, filled in from the end of the row
+    fn table<T: Num + Clone>(&self) -> Vec<Vec<T>> {
+        let (n, m) = (self.springs.len(), self.groups.len());
+        let mut ways = vec![vec![T::zero(); m + 1]; n + 1];
+        ways[n][m] = T::one();
+        for i in (0..n).rev() {
+            for j in 0..=m {
+                let mut w = T::zero();
+                if self.springs[i] != '#' {
+                    w = w + ways[i + 1][j].clone();
                 }
-            })
-            .fold(vec![], |v, j| [v, j].concat())
+                if let Some(end) = self.group_end(i, j) {
+                    w = w + ways[end][j + 1].clone();
+                }
+                ways[i][j] = w;
+            }
+        }
+        ways
     }
-}
 
-fn get_combinations_verify_wise(input: &str) -> u64 {
-    let mut s = input.split(" ");
-    let fst = s.next().unwrap();
-    let snd = s.next().unwrap();
+    fn count<T: Num + Clone>(&self) -> T {
+        self.table::<T>().swap_remove(0).swap_remove(0)
+    }
 
-    let sequence = iter::once('.')
-        .chain(fst.chars().chain(iter::once('.')))
-        .collect::<Vec<char>>();
-    let verify = snd
-        .split(",")
-        .map(|c| c.parse::<u32>().unwrap())
-        .collect::<Vec<u32>>();
+    /// Every arrangement in lexicographic order, with `#` before `.`, worked out as they're
+    /// needed
+    fn arrangements(&self) -> Arrangements<'_> {
+        let ways = self.table::<u128>();
+        let stack = if ways[0][0] > 0 {
+            vec![(0, 0, String::new())]
+        } else {
+            vec![]
+        };
+        Arrangements {
+            row: self,
+            ways,
+            stack,
+        }
+    }
+
+    /// The arrangement `k` places along in lexicographic order, starting from 0
+    fn kth_arrangement(&self, mut k: u128) -> Option<String> {
+        let ways = self.table::<u128>();
+        if k >= ways[0][0] {
+            return None;
+        }
+        let (mut i, mut j) = (0, 0);
+        let mut arrangement = String::new();
+        while i < self.springs.len() {
+            let damaged = self.group_end(i, j).map_or(0, |end| ways[end][j + 1]);
+            if k < damaged {
+                let end = self.group_end(i, j).unwrap();
+                arrangement.extend(iter::repeat_n('#', self.groups[j]));
+                if end > i + self.groups[j] {
+                    arrangement.push('.');
+                }
+                (i, j) = (end, j + 1);
+            } else {
+                k -= damaged;
+                arrangement.push('.');
+                i += 1;
+            }
+        }
+        Some(arrangement)
+    }
 
-    let mut cache = HashMap::new();
-    get_combinations_verify_wise_sub(&mut cache, &sequence, &verify, 1)
+    /// An arrangement chosen uniformly at random, where `random_below(n)` gives a uniformly
+    /// random number less than `n`
+    fn random_arrangement<F>(&self, random_below: F) -> Option<String>
+    where
+        F: FnOnce(u128) -> u128,
+    {
+        let total = self.count::<u128>();
+        if total == 0 {
+            return None;
+        }
+        self.kth_arrangement(random_below(total))
+    }
 }
 
-fn get_combinations_verify_wise_blown_up(input: &&str) -> u64 {
-    let mut s = input.split(" ");
-    let fst = s.next().unwrap();
-    let snd = s.next().unwrap();
-    let fst = iter::repeat(fst).take(5).collect::<Vec<&str>>().join("?");
-    let snd = iter::repeat(snd).take(5).collect::<Vec<&str>>().join(",");
+struct Arrangements<'a> {
+    row: &'a SpringRow,
+    ways: Vec<Vec<u128>>,
+    // Partial arrangements still to be finished, each only pushed if it can be
+    stack: Vec<(usize, usize, String)>,
+}
 
-    let sequence = iter::once('.')
-        .chain(fst.chars().chain(iter::once('.')))
-        .collect::<Vec<char>>();
-    let verify = snd
-        .split(",")
-        .map(|c| c.parse::<u32>().unwrap())
-        .collect::<Vec<u32>>();
+impl Iterator for Arrangements<'_> {
+    type Item = String;
 
-    let mut cache = HashMap::new();
-    get_combinations_verify_wise_sub(&mut cache, &sequence, &verify, 1)
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((i, j, arrangement)) = self.stack.pop() {
+            if i == self.row.springs.len() {
+                return Some(arrangement);
+            }
+            if self.row.springs[i] != '#' && self.ways[i + 1][j] > 0 {
+                self.stack.push((i + 1, j, arrangement.clone() + "."));
+            }
+            if let Some(end) = self.row.group_end(i, j) {
+                if self.ways[end][j + 1] > 0 {
+                    let mut damaged = arrangement;
+                    damaged.extend(iter::repeat_n('#', self.row.groups[j]));
+                    if end > i + self.row.groups[j] {
+                        damaged.push('.');
+                    }
+                    self.stack.push((end, j + 1, damaged));
+                }
+            }
+        }
+        None
+    }
 }
 
 pub struct Task12;
 
 impl TaskCompleter for Task12 {
-
     fn do_task_1(&self) -> String {
         let contents: &str = include_str!("../input/day_12/input");
         contents
             .lines()
-            .map(get_combinations_verify_wise)
-            .sum::<u64>()
+            .map(|x| SpringRow::parse(x).count::<u128>())
+            .sum::<u128>()
             .to_string()
     }
 
     fn do_task_2(&self) -> String {
         let contents = include_str!("../input/day_12/input");
-        // let pb = indicatif::ProgressBar::new(1000);
-        zip(0.., contents.lines())
-            .collect::<Vec<(i32, &str)>>()
-            .par_iter()
-            .map(|(i, x)| {
-                let start: Instant = Instant::now();
-                let v = get_combinations_verify_wise_blown_up(x);
-                if start.elapsed() > Duration::from_secs(20) {
-                    println!("Line {} took {:?}", i + 1, start.elapsed());
-                }
-                // pb.inc(1);
-                v
-            })
-            .sum::<u64>()
+        contents
+            .lines()
+            .map(|x| SpringRow::parse(x).unfold(5).count::<u128>())
+            .sum::<u128>()
             .to_string()
     }
 
@@ -286,3 +298,105 @@ impl TaskCompleter for Task12 {
         Some("850504257483930".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use num::BigUint;
+
+    use crate::day_12::{get_combinations_brute_force, verify_sequence, SpringRow};
+
+    const EXAMPLE: [&str; 6] = [
+        "???.### 1,1,3",
+        ".??..??...?##. 1,1,3",
+        "?#?#?#?#?#?#?#? 1,3,1,6",
+        "????.#...#... 4,1,1",
+        "????.######..#####. 1,6,5",
+        "?###???????? 3,2,1",
+    ];
+
+    #[test]
+    fn counts() {
+        let counts: Vec<u128> = EXAMPLE
+            .iter()
+            .map(|x| SpringRow::parse(x).count())
+            .collect();
+        assert_eq!(counts, vec![1, 4, 1, 1, 4, 10]);
+        let unfolded: Vec<u128> = EXAMPLE
+            .iter()
+            .map(|x| SpringRow::parse(x).unfold(5).count())
+            .collect();
+        assert_eq!(unfolded, vec![1, 16384, 1, 16, 2500, 506250]);
+        for line in EXAMPLE {
+            assert_eq!(
+                SpringRow::parse(line).count::<u128>(),
+                get_combinations_brute_force(line) as u128
+            );
+        }
+    }
+
+    #[test]
+    fn big_counts() {
+        let row = SpringRow::parse("?###???????? 3,2,1").unfold(12);
+        assert_eq!(row.count::<BigUint>(), BigUint::from(row.count::<u128>()));
+        let row = SpringRow::parse("??????????? 1").unfold(60);
+        let wide: BigUint = row.count();
+        assert!(wide > BigUint::from(u128::MAX));
+    }
+
+    #[test]
+    fn enumerate_arrangements() {
+        let row = SpringRow::parse(".??..??...?##. 1,1,3").unfold(2);
+        let all: Vec<String> = row.arrangements().collect();
+        assert_eq!(all.len() as u128, row.count::<u128>());
+        let mut sorted = all.clone();
+        sorted.sort();
+        assert_eq!(all, sorted);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+        for (k, arrangement) in all.iter().enumerate() {
+            assert_eq!(arrangement.len(), row.springs.len());
+            assert!(fits_springs(arrangement, &row.springs));
+            let mut sequence: Vec<char> = arrangement.chars().collect();
+            sequence.push('.');
+            let groups: Vec<u32> = row.groups.iter().map(|x| *x as u32).collect();
+            assert!(verify_sequence(&sequence, &groups));
+            assert_eq!(row.kth_arrangement(k as u128).as_ref(), Some(arrangement));
+        }
+        assert_eq!(row.kth_arrangement(all.len() as u128), None);
+
+        let row = SpringRow::parse("???.### 1,1,3");
+        assert_eq!(row.arrangements().collect::<Vec<_>>(), vec!["#.#.###"]);
+        assert_eq!(SpringRow::parse("#.# 2").arrangements().next(), None);
+    }
+
+    #[test]
+    fn random_arrangements() {
+        let row = SpringRow::parse("?###???????? 3,2,1");
+        let all: HashSet<String> = row.arrangements().collect();
+        // A small xorshift generator is plenty to pick arrangements for a test
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut seen = HashSet::new();
+        for _ in 0..200 {
+            let arrangement = row
+                .random_arrangement(|n| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    state as u128 % n
+                })
+                .unwrap();
+            assert!(all.contains(&arrangement));
+            seen.insert(arrangement);
+        }
+        assert_eq!(seen, all);
+        assert_eq!(SpringRow::parse("#.# 2").random_arrangement(|_| 0), None);
+    }
+
+    fn fits_springs(arrangement: &str, springs: &[char]) -> bool {
+        arrangement
+            .chars()
+            .zip(springs)
+            .all(|(a, s)| *s == '?' || a == *s)
+    }
+}
@@ -105,117 +105,94 @@ impl Grid {
         );
     }
 
-    fn get_smudges(&self) -> Vec<Grid> {
-        let mut v = vec![];
-        // Get current reflective line
-        // let horizontal_range;
-        // let vertical_range;
-        // match get_horizontal_reflection(self)
-        //     .or_else(|| get_vertical_reflection(self))
-        //     .unwrap()
-        // {
-        //     ReflectiveLine::Horizontal(x) => {
-        //         horizontal_range = 0..self.width();
-        //         let gap = (self.height() - x).min(x);
-        //         vertical_range = (x - gap)..(x + gap);
-        //     }
-        //     ReflectiveLine::Vertical(y) => {
-        //         vertical_range = 0..self.height();
-        //         let gap = (self.width() - y).min(y);
-        //         horizontal_range = (y - gap)..(y + gap);
-        //     }
-        // }
-        // dbg!(&horizontal_range);
-        // dbg!(&vertical_range);
-        let horizontal_range = 0..self.width();
-        let vertical_range = 0..self.height();
-
-        for i in horizontal_range.to_owned() {
-            for j in vertical_range.to_owned() {
-                let mut new_g = self.clone();
-                new_g[(i, j)].invert();
-                v.push(new_g);
+    /// Each row and each column as a bitmask with a bit set for every rock, split into as many
+    /// 64 bit words as the line needs
+    fn bitmasks(&self) -> (Vec<Vec<u64>>, Vec<Vec<u64>>) {
+        let mut rows = vec![vec![0; self.width().div_ceil(64)]; self.height()];
+        let mut columns = vec![vec![0; self.height().div_ceil(64)]; self.width()];
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if self[(x, y)] == Tile::Rock {
+                    rows[y][x / 64] |= 1 << (x % 64);
+                    columns[x][y / 64] |= 1 << (y % 64);
+                }
             }
         }
-        v
+        (rows, columns)
     }
 }
 
-fn verify_horizontal_reflection(grid: &Grid, start: usize, end: usize) -> Option<usize> {
-    assert_eq!((end - start) % 2, 1);
-
-    // Check correct
-    for i in 0..grid.width() {
-        if grid[(i, start)] != grid[(i, end)] {
-            return None;
-        }
-    }
-
-    // Return value
-    if start == end - 1 {
-        Some(start)
-    } else {
-        verify_horizontal_reflection(grid, start + 1, end - 1)
-    }
+/// A line of reflection, and the smudges that stop it being exact. Each smudge is a pair of
+/// cells, as (x, y), that are mirror images but differ, and fixing either one of them will do.
+#[derive(Debug, PartialEq, Eq)]
+struct Reflection {
+    line: ReflectiveLine,
+    smudges: Vec<((usize, usize), (usize, usize))>,
 }
 
-fn verify_vertical_reflection(grid: &Grid, start: usize, end: usize) -> Option<usize> {
-    assert_eq!((end - start) % 2, 1);
-
-    // Check correct
-    for i in 0..grid.height() {
-        if grid[(start, i)] != grid[(end, i)] {
-            return None;
-        }
-    }
-
-    // Return value
-    if start == end - 1 {
-        Some(start)
-    } else {
-        verify_vertical_reflection(grid, start + 1, end - 1)
-    }
-}
+// A bit that differs between two mirrored lines, as (line before, line after, bit)
+type Difference = (usize, usize, usize);
 
-fn get_horizontal_reflections(grid: &Grid) -> Vec<ReflectiveLine> {
+// Positions between lines that reflect the rest with exactly `smudges` bits different, along
+// with the differing bits
+fn axis_reflections(lines: &[Vec<u64>], smudges: usize) -> Vec<(usize, Vec<Difference>)> {
     let mut v = vec![];
-    // From start
-    for i in (1..grid.height()).step_by(2) {
-        let r = verify_horizontal_reflection(grid, 0, i);
-        if r != None {
-            v.push(ReflectiveLine::Horizontal(r.unwrap() + 1));
+    for position in 1..lines.len() {
+        let mut found = vec![];
+        'pairs: for (a, b) in (0..position).rev().zip(position..lines.len()) {
+            for (word, (x, y)) in lines[a].iter().zip(&lines[b]).enumerate() {
+                let mut diff = x ^ y;
+                while diff != 0 {
+                    found.push((a, b, word * 64 + diff.trailing_zeros() as usize));
+                    if found.len() > smudges {
+                        break 'pairs;
+                    }
+                    diff &= diff - 1;
+                }
+            }
         }
-    }
-    let start = if grid.height() % 2 == 0 { 0 } else { 1 };
-    for i in (start..grid.height()).step_by(2) {
-        let r = verify_horizontal_reflection(grid, i, grid.height() - 1);
-        if r != None {
-            v.push(ReflectiveLine::Horizontal(r.unwrap() + 1));
+        if found.len() == smudges {
+            v.push((position, found));
         }
     }
-    v.dedup();
     v
 }
 
-fn get_vertical_reflections(grid: &Grid) -> Vec<ReflectiveLine> {
-    let mut v = vec![];
+/// Every line of reflection that needs exactly `smudges` cells fixed, vertical lines first
+fn find_reflections(grid: &Grid, smudges: usize) -> Vec<Reflection> {
+    let (rows, columns) = grid.bitmasks();
+    let vertical = axis_reflections(&columns, smudges)
+        .into_iter()
+        .map(|(position, found)| Reflection {
+            line: ReflectiveLine::Vertical(position),
+            smudges: found
+                .into_iter()
+                .map(|(a, b, y)| ((a, y), (b, y)))
+                .collect(),
+        });
+    let horizontal = axis_reflections(&rows, smudges)
+        .into_iter()
+        .map(|(position, found)| Reflection {
+            line: ReflectiveLine::Horizontal(position),
+            smudges: found
+                .into_iter()
+                .map(|(a, b, x)| ((x, a), (x, b)))
+                .collect(),
+        });
+    vertical.chain(horizontal).collect()
+}
 
-    // From start
-    for i in (1..grid.width()).step_by(2) {
-        let r = verify_vertical_reflection(grid, 0, i);
-        if r != None {
-            v.push(ReflectiveLine::Vertical(r.unwrap() + 1));
-        }
-    }
-    let start = if grid.width() % 2 == 0 { 0 } else { 1 };
-    for i in (start..grid.width()).step_by(2) {
-        let r = verify_vertical_reflection(grid, i, grid.width() - 1);
-        if r != None {
-            v.push(ReflectiveLine::Vertical(r.unwrap() + 1));
-        }
-    }
-    v.dedup();
-    v
+fn parse_patterns(contents: &str) -> Vec<Grid> {
+    contents.lines().fold(vec![Grid::new()], |mut v, x| {
+        if x.is_empty() {
+            v.push(Grid::new());
+        } else {
+            v.last_mut()
+                .unwrap()
+                .add_line(x.chars().collect::<Vec<char>>())
+        };
+        v
+    })
 }
 
 pub struct Task13;
@@ -223,83 +200,20 @@ pub struct Task13;
 impl TaskCompleter for Task13 {
     fn do_task_1(&self) -> String {
         let contents: &str = include_str!("../input/day_13/input");
-        let mut grids = contents.lines().fold(vec![Grid::new()], |mut v, x| {
-            if x.is_empty() {
-                v.push(Grid::new());
-            } else {
-                v.last_mut()
-                    .unwrap()
-                    .add_line(x.chars().collect::<Vec<char>>())
-            };
-            v
-        });
-
-        grids
-            .iter_mut()
-            .map(|x| {
-                vec![get_vertical_reflections(x), get_horizontal_reflections(x)]
-                    .into_iter()
-                    .flatten()
-            })
-            .flatten()
-            .map(|x| x.get_final_value())
+        parse_patterns(contents)
+            .iter()
+            .flat_map(|x| find_reflections(x, 0))
+            .map(|x| x.line.get_final_value())
             .sum::<usize>()
             .to_string()
     }
 
     fn do_task_2(&self) -> String {
         let contents: &str = include_str!("../input/day_13/input");
-        let mut grids = contents.lines().fold(vec![Grid::new()], |mut v, x| {
-            if x.is_empty() {
-                v.push(Grid::new());
-            } else {
-                v.last_mut()
-                    .unwrap()
-                    .add_line(x.chars().collect::<Vec<char>>())
-            };
-            v
-        });
-
-        grids
-            .iter_mut()
-            .map(|grid| {
-                (
-                    grid.get_smudges(),
-                    // Get the original reflective line
-                    vec![get_vertical_reflections(grid), get_horizontal_reflections(grid)]
-                        .into_iter()
-                        .flatten()
-                        .collect::<Vec<ReflectiveLine>>()[0],
-                )
-            })
-            .map(|(grids, v)| {
-                let all_reflective_lines_for_grid = grids
-                    .iter()
-                    .map(|y| {
-                        get_vertical_reflections(y)
-                            .into_iter()
-                            .chain(get_horizontal_reflections(y).into_iter())
-                            .filter(|x| x != &v)
-                            .collect::<Vec<ReflectiveLine>>()
-                    })
-                    .flatten()
-                    .map(|x| x.get_final_value())
-                    .collect::<Vec<usize>>();
-                // println!("The grid is {}", x[0]);
-                // Make sure all the reflective lines are the same
-                all_reflective_lines_for_grid.into_iter()
-                    .fold(None, |x, y| match x {
-                        Some(t) => {
-                            if t == y {
-                                Some(t)
-                            } else {
-                                panic!("ALl new reflective lines must have same value")
-                            }
-                        }
-                        None => Some(y),
-                    })
-                    .unwrap()
-            })
+        parse_patterns(contents)
+            .iter()
+            .flat_map(|x| find_reflections(x, 1))
+            .map(|x| x.line.get_final_value())
             .sum::<usize>()
             .to_string()
     }
@@ -312,3 +226,98 @@ impl TaskCompleter for Task13 {
         Some("28475".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_13::{find_reflections, parse_patterns, ReflectiveLine};
+
+    const EXAMPLE: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#
+";
+
+    #[test]
+    fn example_reflections() {
+        let patterns = parse_patterns(EXAMPLE);
+        let exact: Vec<ReflectiveLine> = patterns
+            .iter()
+            .flat_map(|x| find_reflections(x, 0))
+            .map(|x| x.line)
+            .collect();
+        assert_eq!(
+            exact,
+            vec![ReflectiveLine::Vertical(5), ReflectiveLine::Horizontal(4)]
+        );
+
+        let first = find_reflections(&patterns[0], 1);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].line, ReflectiveLine::Horizontal(3));
+        assert_eq!(first[0].smudges, vec![((0, 0), (0, 5))]);
+        let second = find_reflections(&patterns[1], 1);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].line, ReflectiveLine::Horizontal(1));
+        assert_eq!(second[0].smudges, vec![((4, 0), (4, 1))]);
+    }
+
+    #[test]
+    fn fixing_smudges_gives_exact_reflection() {
+        for pattern in parse_patterns(EXAMPLE) {
+            for smudges in 0..4 {
+                for reflection in find_reflections(&pattern, smudges) {
+                    assert_eq!(reflection.smudges.len(), smudges);
+                    let mut fixed = pattern.clone();
+                    for (cell, _) in &reflection.smudges {
+                        fixed[*cell].invert();
+                    }
+                    assert!(find_reflections(&fixed, 0)
+                        .iter()
+                        .any(|x| x.line == reflection.line));
+                }
+            }
+        }
+    }
+    #[test]
+    fn patterns_over_64_cells() {
+        // 70 wide, mirrored about the line after column 68 so the pairs span two words
+        let row = |y: usize| -> String {
+            let mut cells: Vec<char> = (0..70)
+                .map(|x| if (x * x + y) % 3 == 1 { '#' } else { '.' })
+                .collect();
+            cells[68] = cells[67];
+            cells[69] = cells[66];
+            cells.into_iter().collect()
+        };
+        let wide = (0..3).map(row).collect::<Vec<_>>().join("\n");
+        let tall = (0..70)
+            .map(|x| {
+                (0..3)
+                    .map(|y| row(y).as_bytes()[x] as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        for (input, line, smudge) in [
+            (wide, ReflectiveLine::Vertical(68), ((66, 1), (69, 1))),
+            (tall, ReflectiveLine::Horizontal(68), ((1, 66), (1, 69))),
+        ] {
+            let mut pattern = parse_patterns(&input).remove(0);
+            assert!(find_reflections(&pattern, 0).iter().any(|x| x.line == line));
+            pattern[smudge.1].invert();
+            let found = find_reflections(&pattern, 1);
+            let reflection = found.iter().find(|x| x.line == line).unwrap();
+            assert_eq!(reflection.smudges, vec![smudge]);
+        }
+    }
+}
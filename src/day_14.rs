@@ -1,5 +1,4 @@
 use std::{
-    collections::HashMap,
     fmt::Display,
     iter::zip,
     ops::{Index, IndexMut},
@@ -7,7 +6,7 @@ use std::{
 
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 
-use crate::TaskCompleter;
+use crate::{cycle::find_cycle, TaskCompleter};
 
 #[derive(PartialEq, Eq, Clone, Debug, Hash)]
 enum Direction {
@@ -50,17 +49,12 @@ struct Grid {
 
 impl Display for Grid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut str = "\n".to_owned();
-        for y in 0..self.height() {
-            let mut l = "".to_owned();
-
-            for x in 0..self.width() {
-                l += self[(x, y)].to_char();
-            }
-            str += &l;
-            str += "\n";
-        }
-        f.write_str(&str)
+        let rows: Vec<String> = self
+            .g
+            .iter()
+            .map(|row| row.iter().map(Tile::to_char).collect())
+            .collect();
+        f.write_str(&rows.join("\n"))
     }
 }
 
@@ -217,32 +211,18 @@ impl TaskCompleter for Task14 {
 
     fn do_task_2(&self) -> String {
         let contents: &str = include_str!("../input/day_14/input");
-        let mut grid = Grid::new(contents);
+        let grid = Grid::new(contents);
         const TOTAL_ITERS: u64 = 1000000000;
-        let mut seen_before = HashMap::<Grid, u64>::new();
-        let mut start_of_loop = 0;
-        let mut length_of_loop = 0;
-        for i in 0..TOTAL_ITERS {
-            if let Some(index) = seen_before.get(&grid) {
-                start_of_loop = *index;
-                length_of_loop = i - index;
-                break;
-            } else {
-                seen_before.insert(grid.clone(), i);
-            }
-            grid.move_direction(Direction::North);
-            grid.move_direction(Direction::West);
-            grid.move_direction(Direction::South);
-            grid.move_direction(Direction::East);
-        }
-        let position = (TOTAL_ITERS - start_of_loop) % length_of_loop;
-        for _ in 0..position {
+        let spin = |grid: &mut Grid| {
             grid.move_direction(Direction::North);
             grid.move_direction(Direction::West);
             grid.move_direction(Direction::South);
             grid.move_direction(Direction::East);
-        }
-        grid.get_load_value().to_string()
+        };
+        let cycle = find_cycle(&grid, spin);
+        cycle
+            .value_after(grid, spin, TOTAL_ITERS, Grid::get_load_value)
+            .to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -253,3 +233,49 @@ impl TaskCompleter for Task14 {
         Some("102657".to_owned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::day_14::{Direction, Grid};
+
+    fn assert_grid_eq(grid: &Grid, expected: &str) {
+        let actual: crate::grid::Grid<char> = grid.to_string().parse().unwrap();
+        let expected: crate::grid::Grid<char> = expected.parse().unwrap();
+        assert!(actual == expected, "\n{}", actual.diff(&expected));
+    }
+
+    #[test]
+    fn tilts_on_example() {
+        let mut grid = Grid::new(include_str!("../input/day_14/example"));
+        grid.move_direction(Direction::North);
+        assert_grid_eq(
+            &grid,
+            "OOOO.#.O..
+OO..#....#
+OO..O##..O
+O..#.OO...
+........#.
+..#....#.#
+..O..#.O.O
+..O.......
+#....###..
+#....#....",
+        );
+        grid.move_direction(Direction::West);
+        grid.move_direction(Direction::South);
+        grid.move_direction(Direction::East);
+        assert_grid_eq(
+            &grid,
+            ".....#....
+....#...O#
+...OO##...
+.OO#......
+.....OOO#.
+.O#...O#.#
+....O#....
+......OOOO
+#...O###..
+#..OO#....",
+        );
+    }
+}
@@ -1,66 +1,16 @@
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
-    grid::{Coord, Direction, Grid},
+    grid::{bitgrid::BitPlanes, Coord, Direction, Grid},
     TaskCompleter,
 };
 
-#[derive(Default, Clone)]
-struct LightRay {
-    up: bool,
-    down: bool,
-    left: bool,
-    right: bool,
-}
-impl LightRay {
-    // Returns true if already set
-    fn set(&mut self, direction: Direction) -> bool {
-        match direction {
-            Direction::Up => {
-                if self.up {
-                    true
-                } else {
-                    self.up = true;
-                    false
-                }
-            }
-            Direction::Down => {
-                if self.down {
-                    true
-                } else {
-                    self.down = true;
-                    false
-                }
-            }
-            Direction::Left => {
-                if self.left {
-                    true
-                } else {
-                    self.left = true;
-                    false
-                }
-            }
-            Direction::Right => {
-                if self.right {
-                    true
-                } else {
-                    self.right = true;
-                    false
-                }
-            }
-        }
-    }
-
-    fn is_energized(&self) -> bool {
-        self.down || self.up || self.right || self.left
-    }
-}
-
-fn follow_light_ray(chars: &Grid<char>, init_from: Coord, init_dir: Direction) -> Grid<LightRay> {
-    let mut rays: Grid<LightRay> = Grid::default_with_size(chars.width(), chars.height());
+// One plane per direction a ray has passed through each tile in
+fn follow_light_ray(chars: &Grid<char>, init_from: Coord, init_dir: Direction) -> BitPlanes {
+    let mut rays = BitPlanes::new(4, chars.width(), chars.height());
     let mut to_check = vec![(init_from, init_dir)];
     while let Some((from, direction)) = to_check.pop() {
-        if chars.in_bounds(from) && rays[from].set(direction) {
+        if chars.in_bounds(from) && rays.set(from, direction as usize, true) {
             continue;
         }
         if let Some(new_coord) = from.translate(direction, chars) {
@@ -120,12 +70,7 @@ impl TaskCompleter for Task16 {
             Coord::new(-1, chars.height() as i64 - 1),
             Direction::Right,
         );
-        rays.grid()
-            .iter()
-            .flatten()
-            .filter(|x| x.is_energized())
-            .count()
-            .to_string()
+        rays.union().count_ones().to_string()
     }
 
     fn do_task_2(&self) -> String {
@@ -152,11 +97,7 @@ impl TaskCompleter for Task16 {
             )
             .map(|(c, d)| {
                 let rays = follow_light_ray(&chars, c, d);
-                rays.grid()
-                    .iter()
-                    .flatten()
-                    .filter(|x| x.is_energized())
-                    .count()
+                rays.union().count_ones()
             })
             .max()
             .unwrap()
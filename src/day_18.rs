@@ -1,7 +1,7 @@
 use std::iter;
 
 use crate::{
-    grid::{Direction},
+    grid::{compressed::CompressedGrid, Coord, Direction, SparseGrid},
     TaskCompleter,
 };
 
@@ -60,6 +60,54 @@ fn parse_line_part2(line: &str) -> (Direction, i64) {
     (dir, distance)
 }
 
+// Draw the trench onto a canvas with a one tile border, going down the page for Direction::Down
+fn dig_trench(input: &str) -> SparseGrid<char> {
+    let mut canvas = SparseGrid::new('.');
+    let mut pos = Coord::new(0, 0);
+    canvas[pos] = '#';
+    for line in input.lines() {
+        let (dir, distance) = parse_line(line);
+        for _ in 0..distance {
+            pos = match dir {
+                Direction::Up => Coord::new(pos.x(), pos.y() - 1),
+                Direction::Down => Coord::new(pos.x(), pos.y() + 1),
+                Direction::Left => Coord::new(pos.x() - 1, pos.y()),
+                Direction::Right => Coord::new(pos.x() + 1, pos.y()),
+            };
+            canvas[pos] = '#';
+        }
+    }
+    let (min, max) = canvas.bounds().unwrap();
+    canvas.grow_to(Coord::new(min.x() - 1, min.y() - 1));
+    canvas.grow_to(Coord::new(max.x() + 1, max.y() + 1));
+    canvas
+}
+
+// The cells where the trench turns, going down the page for Direction::Down
+fn get_vertices<F>(input: &str, parse_line_func: F) -> Vec<Coord>
+where
+    F: Fn(&str) -> (Direction, i64),
+{
+    let mut pos = Coord::new(0, 0);
+    let mut vertices = vec![pos];
+    for line in input.lines() {
+        let (dir, distance) = parse_line_func(line);
+        pos = match dir {
+            Direction::Up => Coord::new(pos.x(), pos.y() - distance),
+            Direction::Down => Coord::new(pos.x(), pos.y() + distance),
+            Direction::Left => Coord::new(pos.x() - distance, pos.y()),
+            Direction::Right => Coord::new(pos.x() + distance, pos.y()),
+        };
+        vertices.push(pos);
+    }
+    assert_eq!(vertices.pop(), Some(Coord::new(0, 0)));
+    vertices
+}
+
+fn solve_by_compression(vertices: &[Coord]) -> i64 {
+    CompressedGrid::from_polygon(vertices, '#', '.').enclosed_area('.', 'O')
+}
+
 fn get_edges<F>(input: &str, parse_line_func: F) -> (Vec<(i64, i64)>, Vec<(i64, i64)>)
 where
     F: Fn(&str) -> (Direction, i64),
@@ -172,9 +220,7 @@ impl TaskCompleter for Task18 {
 
     fn do_task_2(&self) -> String {
         let contents: &str = include_str!("../input/day_18/input");
-        let (left, right) = get_edges(contents, parse_line_part2);
-        let res2 = solve_by_vertices(&left).max(solve_by_vertices(&right));
-        res2.to_string()
+        solve_by_compression(&get_vertices(contents, parse_line_part2)).to_string()
     }
 
     fn task_1_result(&self) -> Option<String> {
@@ -182,6 +228,32 @@ impl TaskCompleter for Task18 {
     }
 
     fn task_2_result(&self) -> Option<String> {
-        None
+        Some("85070763635666".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_18::{
+        dig_trench, get_vertices, parse_line, parse_line_part2, solve_by_compression,
+    };
+
+    #[test]
+    fn dig_example_trench() {
+        let canvas = dig_trench(include_str!("../input/day_18/example"));
+        assert_eq!(canvas.len(), 38);
+        assert_eq!(
+            canvas.to_string().trim(),
+            include_str!("../output.txt").trim()
+        );
+    }
+
+    #[test]
+    fn compression_on_example() {
+        let contents = include_str!("../input/day_18/example");
+        let vertices = get_vertices(contents, parse_line);
+        assert_eq!(solve_by_compression(&vertices), 62);
+        let vertices = get_vertices(contents, parse_line_part2);
+        assert_eq!(solve_by_compression(&vertices), 952408144115);
     }
 }
@@ -5,7 +5,10 @@ use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 
 use crate::{
-    grid::{Coord, Grid},
+    grid::{
+        grid3::{Axis, Coord3, Cuboid},
+        Coord, Grid,
+    },
     TaskCompleter,
 };
 
@@ -103,15 +106,29 @@ impl Brick {
         fell
     }
 
+    fn cuboid(&self) -> Cuboid {
+        match self {
+            Brick::Cube(x, y, z) => Cuboid::new(Coord3::new(*x, *y, *z), Coord3::new(*x, *y, *z)),
+            Brick::X(xs, y, z) => Cuboid::new(
+                Coord3::new(xs.start, *y, *z),
+                Coord3::new(xs.end - 1, *y, *z),
+            ),
+            Brick::Y(x, ys, z) => Cuboid::new(
+                Coord3::new(*x, ys.start, *z),
+                Coord3::new(*x, ys.end - 1, *z),
+            ),
+            Brick::Z(x, y, zs) => Cuboid::new(
+                Coord3::new(*x, *y, zs.start),
+                Coord3::new(*x, *y, zs.end - 1),
+            ),
+        }
+    }
+
     fn rests_on(&self, y: &Brick) -> bool {
-        let coords_self = match self {
-            Brick::Cube(x, y, z) => vec![(*x, *y, z - 1)],
-            Brick::X(xs, y, z) => xs.clone().map(|x| (x, *y, z - 1)).collect(),
-            Brick::Y(x, ys, z) => ys.clone().map(|y| (*x, y, z - 1)).collect(),
-            Brick::Z(x, y, zs) => vec![(*x, *y, zs.start - 1)],
-        };
-
-        coords_self.iter().any(|x| y.contains(x))
+        self.cuboid()
+            .bottom_face(Axis::Z)
+            .translate(Coord3::new(0, 0, -1))
+            .intersects(&y.cuboid())
     }
 
     fn can_fall_further(&self, bricks: &Vec<Brick>) -> bool {
@@ -120,15 +137,6 @@ impl Brick {
         let is_resting_on_something = bricks.iter().any(|x| self.rests_on(x));
         !is_on_floor && !is_resting_on_something
     }
-
-    fn contains(&self, (t_x, t_y, t_z): &(i64, i64, i64)) -> bool {
-        match self {
-            Brick::Cube(s_x, s_y, s_z) => t_x == s_x && t_y == s_y && t_z == s_z,
-            Brick::X(xs, s_y, s_z) => t_y == s_y && t_z == s_z && xs.clone().any(|x| x == *t_x),
-            Brick::Y(s_x, ys, s_z) => t_x == s_x && t_z == s_z && ys.clone().any(|y| y == *t_y),
-            Brick::Z(s_x, s_y, zs) => t_x == s_x && t_y == s_y && zs.clone().any(|z| z == *t_z),
-        }
-    }
 }
 
 fn get_largest_dimensions(
@@ -1,5 +1,5 @@
 use crate::{
-    grid::{Coord, Direction, Grid},
+    grid::{bitgrid::BitGrid, Coord, Direction, Grid},
     TaskCompleter,
 };
 
@@ -52,11 +52,7 @@ fn get_longest_path(grid: &Grid<char>, start_tile: Coord, end_tile: Coord) -> i6
 fn get_longest_path_2(grid: &Grid<char>, start_tile: Coord, end_tile: Coord) -> i64 {
     let mut queue = VecDeque::new();
     let mut finishing_steps = 0;
-    queue.push_front((
-        start_tile,
-        Grid::init_with_size(false, grid.width(), grid.height()),
-        0,
-    ));
+    queue.push_front((start_tile, BitGrid::new(grid.width(), grid.height()), 0));
     while let Some((tile, mut steps, s)) = queue.pop_back() {
         if tile == end_tile {
             if finishing_steps < s {
@@ -65,7 +61,7 @@ fn get_longest_path_2(grid: &Grid<char>, start_tile: Coord, end_tile: Coord) ->
             }
             continue;
         }
-        steps[tile] = true;
+        steps.set(tile, true);
         let dirs = vec![
             Direction::Up,
             Direction::Down,
@@ -74,7 +70,7 @@ fn get_longest_path_2(grid: &Grid<char>, start_tile: Coord, end_tile: Coord) ->
         ]
         .into_iter()
         .filter_map(|d| tile.translate(d, grid))
-        .filter(|t| grid[*t] != '#' && !steps[*t])
+        .filter(|t| grid[*t] != '#' && !steps.get(*t))
         .collect::<Vec<Coord>>();
         if dirs.len() > 1 {
             for t in &dirs[1..] {
@@ -90,11 +86,7 @@ fn get_longest_path_2(grid: &Grid<char>, start_tile: Coord, end_tile: Coord) ->
 
 fn get_longest_path_multi_threading(grid: &Grid<char>, start_tile: Coord, end_tile: Coord) -> i64 {
     let (s, r) = crossbeam::channel::unbounded();
-    let _ = s.send((
-        start_tile,
-        Grid::init_with_size(false, grid.width(), grid.height()),
-        0,
-    ));
+    let _ = s.send((start_tile, BitGrid::new(grid.width(), grid.height()), 0));
     let grid = Arc::new(grid.clone());
     let threads_completed = Arc::new(Mutex::new(Box::new(0)));
     let mut threads = vec![];
@@ -120,7 +112,7 @@ fn get_longest_path_multi_threading(grid: &Grid<char>, start_tile: Coord, end_ti
                         }
                         continue;
                     }
-                    steps[tile] = true;
+                    steps.set(tile, true);
                     let dirs = vec![
                         Direction::Up,
                         Direction::Down,
@@ -129,7 +121,7 @@ fn get_longest_path_multi_threading(grid: &Grid<char>, start_tile: Coord, end_ti
                     ]
                     .into_iter()
                     .filter_map(|d| tile.translate(d, grid.as_ref()))
-                    .filter(|t| grid[*t] != '#' && !steps[*t])
+                    .filter(|t| grid[*t] != '#' && !steps.get(*t))
                     .collect::<Vec<Coord>>();
                     if dirs.len() > 1 {
                         for t in &dirs[1..] {
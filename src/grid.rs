@@ -3,35 +3,25 @@ use std::{
     ops::{Index, IndexMut},
 };
 
+pub mod bitgrid;
+pub mod compressed;
+pub mod grid3;
+mod iter;
+mod regions;
+pub mod render;
+mod search;
+mod sparse;
+mod text;
+mod tiled;
+
+pub use sparse::SparseGrid;
+pub use tiled::TiledGrid;
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
 pub struct Grid<T> {
     g: Vec<Vec<T>>,
 }
 
-pub struct GridIter<'a, T> {
-    grid: &'a Grid<T>,
-    coord: Coord,
-}
-
-impl<'a, T> Iterator for GridIter<'a, T> {
-    type Item = (Coord, &'a T);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.coord.0 >= self.grid.width() as i64 {
-            self.coord = Coord::new(0, self.coord.1 + 1);
-        }
-        if self.coord.1 >= self.grid.height() as i64 {
-            None
-        } else {
-            let coord = self.coord;
-            let ret = &self.grid[self.coord];
-            self.coord = Coord::new(self.coord.0 + 1, self.coord.1);
-
-            Some((coord, ret))
-        }
-    }
-}
-
 impl Grid<i64> {
     pub fn from_string_i64(input: &str) -> Self {
         let v = input
@@ -59,19 +49,18 @@ impl Grid<char> {
     }
 }
 
+/// Rows top to bottom separated by newlines, with no trailing newline
 impl<T: Display> Display for Grid<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut str = "\n".to_owned();
-        for y in 0..self.height() {
-            let mut l = "".to_owned();
-
-            for x in 0..self.width() {
-                l += &self[Coord(x as i64, y as i64)].to_string();
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                f.write_str("\n")?;
+            }
+            for item in row {
+                item.fmt(f)?;
             }
-            str += &l;
-            str += "\n";
         }
-        f.write_str(&str)
+        Ok(())
     }
 }
 
@@ -106,21 +95,31 @@ impl<T: Clone> Grid<T> {
 }
 
 impl<T: Clone + PartialEq> Grid<T> {
-    pub fn do_flood_fill(&mut self, centre: Coord, to: T, unfilled: T, diagnols: bool) {
+    /// Returns the number of cells filled
+    pub fn do_flood_fill(&mut self, centre: Coord, to: T, unfilled: T, diagnols: bool) -> usize {
+        if to == unfilled || !self.in_bounds(centre) || self[centre] != unfilled {
+            return 0;
+        }
+        self[centre] = to.clone();
+        let mut filled = 1;
         let mut queue = vec![centre];
 
         while let Some(coord) = queue.pop() {
-            if self.in_bounds(coord) && self[coord] == unfilled {
-                self[coord] = to.clone();
-                queue.append(&mut coord.get_neighbours(diagnols));
+            for next in coord.get_neighbours(diagnols) {
+                if self.in_bounds(next) && self[next] == unfilled {
+                    self[next] = to.clone();
+                    filled += 1;
+                    queue.push(next);
+                }
             }
         }
+        filled
     }
 }
 
 impl<T> Grid<T> {
     pub fn width(&self) -> usize {
-        self.g[0].len()
+        self.g.first().map_or(0, Vec::len)
     }
     pub fn height(&self) -> usize {
         self.g.len()
@@ -134,18 +133,11 @@ impl<T> Grid<T> {
         &self.g
     }
 
-    pub fn into_iter<'a>(&'a self) -> GridIter<'a, T> {
-        GridIter {
-            grid: self,
-            coord: Coord::new(0, 0),
-        }
-    }
-
     pub fn find_coord<F>(&self, predicate: F) -> Option<Coord>
     where
         F: Fn(&T) -> bool,
     {
-        for (coord, item) in self.into_iter() {
+        for (coord, item) in self.iter() {
             if predicate(item) {
                 return Some(coord);
             }
@@ -184,6 +176,14 @@ impl Coord {
         Coord(arg1, arg2)
     }
 
+    pub fn x(&self) -> i64 {
+        self.0
+    }
+
+    pub fn y(&self) -> i64 {
+        self.1
+    }
+
     pub(crate) fn non_diagnal_distance(&self, dest: &Coord) -> i64 {
         (self.0.abs_diff(dest.0) + self.1.abs_diff(dest.1)) as i64
     }
@@ -220,6 +220,15 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+    }
+
     pub fn right(&self) -> Self {
         match self {
             Direction::Up => Direction::Right,
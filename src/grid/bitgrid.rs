@@ -0,0 +1,324 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use super::{Coord, Direction, Grid};
+
+/// A grid of booleans packed one bit per cell, with each row starting on a fresh `u64` so
+/// that whole rows can be combined and shifted a word at a time
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_grid<T, F>(grid: &Grid<T>, predicate: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut bits = Self::new(grid.width(), grid.height());
+        for (coord, item) in grid.iter() {
+            if predicate(item) {
+                bits.set(coord, true);
+            }
+        }
+        bits
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, c: Coord) -> bool {
+        c.0 >= 0 && c.0 < self.width as i64 && c.1 >= 0 && c.1 < self.height as i64
+    }
+
+    fn position(&self, c: Coord) -> (usize, u64) {
+        assert!(self.in_bounds(c), "{:?} is outside the grid", c);
+        let x = c.0 as usize;
+        (c.1 as usize * self.words_per_row + x / 64, 1 << (x % 64))
+    }
+
+    pub fn get(&self, c: Coord) -> bool {
+        let (word, bit) = self.position(c);
+        self.bits[word] & bit != 0
+    }
+
+    /// Returns the previous value
+    pub fn set(&mut self, c: Coord, value: bool) -> bool {
+        let (word, bit) = self.position(c);
+        let previous = self.bits[word] & bit != 0;
+        if value {
+            self.bits[word] |= bit;
+        } else {
+            self.bits[word] &= !bit;
+        }
+        previous
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    pub fn any(&self) -> bool {
+        self.bits.iter().any(|x| *x != 0)
+    }
+
+    pub fn iter_ones(&self) -> impl Iterator<Item = Coord> + '_ {
+        (0..self.height).flat_map(move |y| {
+            (0..self.width)
+                .map(move |x| Coord(x as i64, y as i64))
+                .filter(|c| self.get(*c))
+        })
+    }
+
+    fn last_word_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => !0,
+            r => (1 << r) - 1,
+        }
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.bits[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// Move every set bit one cell in `dir`, as `Coord::translate` would. Bits moved off the
+    /// edge of the grid are lost.
+    pub fn shift(&self, dir: Direction) -> BitGrid {
+        let mut shifted = self.clone();
+        let wpr = self.words_per_row;
+        match dir {
+            Direction::Up => {
+                let len = shifted.bits.len();
+                shifted
+                    .bits
+                    .copy_within(0..len.saturating_sub(wpr), wpr.min(len));
+                shifted.bits[..wpr.min(len)].fill(0);
+            }
+            Direction::Down => {
+                shifted.bits.copy_within(wpr.min(self.bits.len()).., 0);
+                let len = shifted.bits.len();
+                shifted.bits[len.saturating_sub(wpr)..].fill(0);
+            }
+            Direction::Right => {
+                let mask = self.last_word_mask();
+                for y in 0..self.height {
+                    let row = shifted.row_mut(y);
+                    for k in (0..wpr).rev() {
+                        let carry = if k > 0 { row[k - 1] >> 63 } else { 0 };
+                        row[k] = (row[k] << 1) | carry;
+                    }
+                    if let Some(last) = row.last_mut() {
+                        *last &= mask;
+                    }
+                }
+            }
+            Direction::Left => {
+                for y in 0..self.height {
+                    let row = shifted.row_mut(y);
+                    for k in 0..wpr {
+                        let carry = if k + 1 < wpr { row[k + 1] << 63 } else { 0 };
+                        row[k] = (row[k] >> 1) | carry;
+                    }
+                }
+            }
+        }
+        shifted
+    }
+
+    /// Every cell one step away from a set cell in any of the four directions
+    pub fn step(&self) -> BitGrid {
+        let mut next = self.shift(Direction::Up);
+        next |= &self.shift(Direction::Down);
+        next |= &self.shift(Direction::Left);
+        next |= &self.shift(Direction::Right);
+        next
+    }
+
+    fn assert_same_size(&self, other: &BitGrid) {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Can only combine bit grids of the same size"
+        );
+    }
+}
+
+impl Not for &BitGrid {
+    type Output = BitGrid;
+
+    fn not(self) -> Self::Output {
+        let mut inverted = self.clone();
+        for word in inverted.bits.iter_mut() {
+            *word = !*word;
+        }
+        let mask = self.last_word_mask();
+        for y in 0..self.height {
+            if let Some(last) = inverted.row_mut(y).last_mut() {
+                *last &= mask;
+            }
+        }
+        inverted
+    }
+}
+
+macro_rules! bit_op {
+    ($trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl $assign_trait<&BitGrid> for BitGrid {
+            fn $assign_fn(&mut self, rhs: &BitGrid) {
+                self.assert_same_size(rhs);
+                for (a, b) in self.bits.iter_mut().zip(rhs.bits.iter()) {
+                    *a = *a $op *b;
+                }
+            }
+        }
+
+        impl $trait<&BitGrid> for &BitGrid {
+            type Output = BitGrid;
+
+            fn $fn(self, rhs: &BitGrid) -> Self::Output {
+                let mut res = self.clone();
+                res.$assign_fn(rhs);
+                res
+            }
+        }
+    };
+}
+
+bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+/// Several `BitGrid`s of the same size, giving each cell a small set of flags such as which
+/// directions it has been entered from
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct BitPlanes {
+    planes: Vec<BitGrid>,
+}
+
+impl BitPlanes {
+    pub fn new(planes: usize, width: usize, height: usize) -> Self {
+        Self {
+            planes: vec![BitGrid::new(width, height); planes],
+        }
+    }
+
+    pub fn plane(&self, plane: usize) -> &BitGrid {
+        &self.planes[plane]
+    }
+
+    pub fn get(&self, c: Coord, plane: usize) -> bool {
+        self.planes[plane].get(c)
+    }
+
+    /// Returns the previous value
+    pub fn set(&mut self, c: Coord, plane: usize, value: bool) -> bool {
+        self.planes[plane].set(c, value)
+    }
+
+    /// Cells with any flag set
+    pub fn union(&self) -> BitGrid {
+        let mut res = self.planes[0].clone();
+        for plane in &self.planes[1..] {
+            res |= plane;
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{bitgrid::BitGrid, Coord, Direction, Grid};
+
+    #[test]
+    fn shifts_across_words() {
+        let mut g = BitGrid::new(70, 3);
+        g.set(Coord::new(63, 1), true);
+        g.set(Coord::new(69, 1), true);
+        let right = g.shift(Direction::Right);
+        assert_eq!(
+            right.iter_ones().collect::<Vec<_>>(),
+            vec![Coord::new(64, 1)]
+        );
+        let left = g.shift(Direction::Left);
+        assert_eq!(
+            left.iter_ones().collect::<Vec<_>>(),
+            vec![Coord::new(62, 1), Coord::new(68, 1)]
+        );
+        assert_eq!(
+            g.shift(Direction::Up).iter_ones().next(),
+            Some(Coord::new(63, 2))
+        );
+        assert_eq!(
+            g.shift(Direction::Down).iter_ones().next(),
+            Some(Coord::new(63, 0))
+        );
+        assert_eq!(g.shift(Direction::Up).shift(Direction::Up).count_ones(), 0);
+    }
+
+    #[test]
+    fn bulk_operations() {
+        let a = BitGrid::from_grid(&Grid::from_string("##.\n.#.", false), |x| x == &'#');
+        let b = BitGrid::from_grid(&Grid::from_string(".##\n.#.", false), |x| x == &'#');
+        assert_eq!((&a & &b).count_ones(), 2);
+        assert_eq!((&a | &b).count_ones(), 4);
+        assert_eq!((&a ^ &b).count_ones(), 2);
+        assert_eq!((!&a).count_ones(), 3);
+    }
+
+    #[test]
+    fn empty_grids() {
+        for (width, height) in [(0, 3), (4, 0), (0, 0)] {
+            let g = BitGrid::new(width, height);
+            for dir in [
+                Direction::Up,
+                Direction::Down,
+                Direction::Left,
+                Direction::Right,
+            ] {
+                assert_eq!(g.shift(dir), g);
+            }
+            assert_eq!(g.step().count_ones(), 0);
+            assert_eq!((!&g).count_ones(), 0);
+            assert!(!g.in_bounds(Coord::new(0, 0)));
+        }
+    }
+
+    #[test]
+    fn steps_match_bfs_on_example() {
+        let g = Grid::from_string(include_str!("../../input/day_21/example"), false);
+        let start = g.find_coord(|x| x == &'S').unwrap();
+        let open = BitGrid::from_grid(&g, |x| x != &'#');
+        let distances = g.get_distances_from(&[start], |x| x != &'#');
+        let mut reachable = BitGrid::new(g.width(), g.height());
+        reachable.set(start, true);
+        for steps in 1..=6 {
+            reachable = &reachable.step() & &open;
+            let expected = distances
+                .iter()
+                .filter(|(_, d)| matches!(d, Some(d) if *d <= steps && d % 2 == steps % 2))
+                .count();
+            assert_eq!(reachable.count_ones(), expected);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use super::{Coord, Grid};
+
+/// A small `Grid` standing in for a huge one, where each cell covers a rectangle of original
+/// cells. Column `i` covers original x values `xs[i]..xs[i + 1]` and likewise for rows.
+#[derive(Clone, Debug)]
+pub struct CompressedGrid<T> {
+    grid: Grid<T>,
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+}
+
+fn breakpoints(values: impl Iterator<Item = i64>) -> Vec<i64> {
+    let mut v: Vec<i64> = values.flat_map(|x| [x, x + 1]).collect();
+    let (min, max) = (*v.iter().min().unwrap(), *v.iter().max().unwrap());
+    // Leave a border so everything outside the polygon is connected
+    v.push(min - 1);
+    v.push(max + 1);
+    v.sort();
+    v.dedup();
+    v
+}
+
+fn compress_value(breakpoints: &[i64], value: i64) -> Option<usize> {
+    let i = breakpoints.partition_point(|x| *x <= value);
+    if i == 0 || i == breakpoints.len() {
+        None
+    } else {
+        Some(i - 1)
+    }
+}
+
+impl<T: Clone> CompressedGrid<T> {
+    /// Compress the closed rectilinear polygon passing through the cells at `vertices`. Cells
+    /// on its boundary are set to `edge` and everything else to `fill`, with a border of `fill`
+    /// all the way around.
+    pub fn from_polygon(vertices: &[Coord], edge: T, fill: T) -> Self {
+        let xs = breakpoints(vertices.iter().map(|c| c.0));
+        let ys = breakpoints(vertices.iter().map(|c| c.1));
+        let grid = Grid::init_with_size(fill, xs.len() - 1, ys.len() - 1);
+        let mut compressed = Self { grid, xs, ys };
+        for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            assert!(
+                a.0 == b.0 || a.1 == b.1,
+                "Edge from {:?} to {:?} isn't axis aligned",
+                a,
+                b
+            );
+            let start = compressed
+                .compress(Coord(a.0.min(b.0), a.1.min(b.1)))
+                .unwrap();
+            let end = compressed
+                .compress(Coord(a.0.max(b.0), a.1.max(b.1)))
+                .unwrap();
+            for y in start.1..=end.1 {
+                for x in start.0..=end.0 {
+                    compressed.grid[Coord(x, y)] = edge.clone();
+                }
+            }
+        }
+        compressed
+    }
+}
+
+impl<T> CompressedGrid<T> {
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn grid_mut(&mut self) -> &mut Grid<T> {
+        &mut self.grid
+    }
+
+    /// The compressed cell containing the original coordinate
+    pub fn compress(&self, original: Coord) -> Option<Coord> {
+        Some(Coord(
+            compress_value(&self.xs, original.0)? as i64,
+            compress_value(&self.ys, original.1)? as i64,
+        ))
+    }
+
+    /// The smallest original coordinate covered by a compressed cell
+    pub fn original(&self, compressed: Coord) -> Coord {
+        Coord(
+            self.xs[compressed.0 as usize],
+            self.ys[compressed.1 as usize],
+        )
+    }
+
+    /// How many original cells a compressed cell covers
+    pub fn weight(&self, compressed: Coord) -> i64 {
+        let (x, y) = (compressed.0 as usize, compressed.1 as usize);
+        (self.xs[x + 1] - self.xs[x]) * (self.ys[y + 1] - self.ys[y])
+    }
+
+    /// Number of original cells covered by compressed cells satisfying `predicate`
+    pub fn total_weight<F>(&self, predicate: F) -> i64
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.grid
+            .iter()
+            .filter(|(_, item)| predicate(item))
+            .map(|(c, _)| self.weight(c))
+            .sum()
+    }
+}
+
+impl<T: Clone + PartialEq> CompressedGrid<T> {
+    /// Number of original cells on or inside the polygon, found by flood filling the outside
+    /// with `outside`
+    pub fn enclosed_area(&mut self, fill: T, outside: T) -> i64 {
+        self.grid
+            .do_flood_fill(Coord(0, 0), outside.clone(), fill, false);
+        self.total_weight(|x| *x != outside)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{compressed::CompressedGrid, Coord};
+
+    #[test]
+    fn compresses_large_rectangle() {
+        let vertices = [
+            Coord::new(0, 0),
+            Coord::new(999_999, 0),
+            Coord::new(999_999, 9),
+            Coord::new(0, 9),
+        ];
+        let mut compressed = CompressedGrid::from_polygon(&vertices, '#', '.');
+        assert_eq!(compressed.grid().width(), 5);
+        assert_eq!(
+            compressed.total_weight(|x| *x == '#'),
+            2 * 1_000_000 + 2 * 8
+        );
+        assert_eq!(compressed.enclosed_area('.', 'O'), 10_000_000);
+    }
+
+    #[test]
+    fn maps_coordinates() {
+        let vertices = [
+            Coord::new(0, 0),
+            Coord::new(10, 0),
+            Coord::new(10, 5),
+            Coord::new(0, 5),
+        ];
+        let compressed = CompressedGrid::from_polygon(&vertices, '#', '.');
+        let c = compressed.compress(Coord::new(4, 3)).unwrap();
+        assert_eq!(compressed.original(c), Coord::new(1, 1));
+        assert_eq!(compressed.weight(c), 9 * 4);
+        assert_eq!(compressed.compress(Coord::new(-5, 0)), None);
+    }
+}
@@ -0,0 +1,278 @@
+use std::ops::{Index, IndexMut};
+
+use super::{Coord, Grid};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, PartialOrd, Ord)]
+pub struct Coord3(i64, i64, i64);
+
+impl Coord3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Self {
+        Self(x, y, z)
+    }
+
+    pub fn x(&self) -> i64 {
+        self.0
+    }
+
+    pub fn y(&self) -> i64 {
+        self.1
+    }
+
+    pub fn z(&self) -> i64 {
+        self.2
+    }
+
+    pub fn offset(&self, by: Coord3) -> Self {
+        Self(self.0 + by.0, self.1 + by.1, self.2 + by.2)
+    }
+
+    /// The 6 face neighbours, or all 26 surrounding coordinates if `diagonals`
+    pub fn get_neighbours(&self, diagonals: bool) -> Vec<Coord3> {
+        let mut v = vec![];
+        for x in -1..=1_i64 {
+            for y in -1..=1_i64 {
+                for z in -1..=1_i64 {
+                    let changed = x.abs() + y.abs() + z.abs();
+                    if changed == 1 || (diagonals && changed > 1) {
+                        v.push(self.offset(Coord3(x, y, z)));
+                    }
+                }
+            }
+        }
+        v
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// An axis-aligned box of coordinates, both corners inclusive
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct Cuboid {
+    min: Coord3,
+    max: Coord3,
+}
+
+impl Cuboid {
+    /// The box with `a` and `b` as opposite corners, in any order
+    pub fn new(a: Coord3, b: Coord3) -> Self {
+        Self {
+            min: Coord3(a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+            max: Coord3(a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+        }
+    }
+
+    pub fn min(&self) -> Coord3 {
+        self.min
+    }
+
+    pub fn max(&self) -> Coord3 {
+        self.max
+    }
+
+    pub fn volume(&self) -> i64 {
+        (self.max.0 - self.min.0 + 1)
+            * (self.max.1 - self.min.1 + 1)
+            * (self.max.2 - self.min.2 + 1)
+    }
+
+    pub fn contains(&self, c: Coord3) -> bool {
+        (self.min.0..=self.max.0).contains(&c.0)
+            && (self.min.1..=self.max.1).contains(&c.1)
+            && (self.min.2..=self.max.2).contains(&c.2)
+    }
+
+    pub fn intersects(&self, other: &Cuboid) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        let min = Coord3(
+            self.min.0.max(other.min.0),
+            self.min.1.max(other.min.1),
+            self.min.2.max(other.min.2),
+        );
+        let max = Coord3(
+            self.max.0.min(other.max.0),
+            self.max.1.min(other.max.1),
+            self.max.2.min(other.max.2),
+        );
+        if min.0 <= max.0 && min.1 <= max.1 && min.2 <= max.2 {
+            Some(Cuboid { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn translate(&self, by: Coord3) -> Cuboid {
+        Cuboid {
+            min: self.min.offset(by),
+            max: self.max.offset(by),
+        }
+    }
+
+    /// The one thick layer of the box with the smallest coordinate along `axis`
+    pub fn bottom_face(&self, axis: Axis) -> Cuboid {
+        let mut max = self.max;
+        match axis {
+            Axis::X => max.0 = self.min.0,
+            Axis::Y => max.1 = self.min.1,
+            Axis::Z => max.2 = self.min.2,
+        }
+        Cuboid { min: self.min, max }
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = Coord3> + '_ {
+        (self.min.2..=self.max.2).flat_map(move |z| {
+            (self.min.1..=self.max.1)
+                .flat_map(move |y| (self.min.0..=self.max.0).map(move |x| Coord3(x, y, z)))
+        })
+    }
+}
+
+/// A dense three dimensional grid, stored as one `Grid` layer per z
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
+pub struct Grid3<T> {
+    layers: Vec<Grid<T>>,
+}
+
+impl<T: Clone> Grid3<T> {
+    pub fn init_with_size(init_value: T, width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            layers: vec![Grid::init_with_size(init_value, width, height); depth],
+        }
+    }
+
+    /// A two dimensional cross section at `index` along `axis`. Slicing along `Axis::Z` gives
+    /// a layer indexed by (x, y), along `Axis::Y` by (x, z) and along `Axis::X` by (y, z).
+    pub fn slice(&self, axis: Axis, index: usize) -> Grid<T> {
+        match axis {
+            Axis::Z => self.layers[index].clone(),
+            Axis::Y => self.layers.iter().map(|l| l.row(index).to_vec()).collect(),
+            Axis::X => self
+                .layers
+                .iter()
+                .map(|l| l.column(index).cloned().collect::<Vec<T>>())
+                .collect(),
+        }
+    }
+}
+
+impl<T: Default + Clone> Grid3<T> {
+    pub fn default_with_size(width: usize, height: usize, depth: usize) -> Self {
+        Self::init_with_size(T::default(), width, height, depth)
+    }
+}
+
+impl<T> Grid3<T> {
+    pub fn width(&self) -> usize {
+        self.layers[0].width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.layers[0].height()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn in_bounds(&self, c: Coord3) -> bool {
+        c.2 >= 0 && c.2 < self.depth() as i64 && self.layers[0].in_bounds(Coord(c.0, c.1))
+    }
+
+    pub fn bounds(&self) -> Cuboid {
+        Cuboid {
+            min: Coord3(0, 0, 0),
+            max: Coord3(
+                self.width() as i64 - 1,
+                self.height() as i64 - 1,
+                self.depth() as i64 - 1,
+            ),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord3, &T)> {
+        self.layers.iter().enumerate().flat_map(|(z, l)| {
+            l.iter()
+                .map(move |(c, item)| (Coord3(c.0, c.1, z as i64), item))
+        })
+    }
+
+    /// The neighbours of `coord` which are inside the grid
+    pub fn neighbours(&self, coord: Coord3, diagonals: bool) -> impl Iterator<Item = (Coord3, &T)> {
+        coord
+            .get_neighbours(diagonals)
+            .into_iter()
+            .filter(|x| self.in_bounds(*x))
+            .map(|x| (x, &self[x]))
+    }
+}
+
+impl<T> Index<Coord3> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, index: Coord3) -> &Self::Output {
+        &self.layers[index.2 as usize][Coord(index.0, index.1)]
+    }
+}
+
+impl<T> IndexMut<Coord3> for Grid3<T> {
+    fn index_mut(&mut self, index: Coord3) -> &mut Self::Output {
+        &mut self.layers[index.2 as usize][Coord(index.0, index.1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::grid3::{Axis, Coord3, Cuboid, Grid3};
+
+    #[test]
+    fn neighbourhoods() {
+        let c = Coord3::new(0, 0, 0);
+        assert_eq!(c.get_neighbours(false).len(), 6);
+        assert_eq!(c.get_neighbours(true).len(), 26);
+        let g = Grid3::init_with_size(0, 3, 3, 3);
+        assert_eq!(g.neighbours(c, false).count(), 3);
+        assert_eq!(g.neighbours(c, true).count(), 7);
+        assert_eq!(g.neighbours(Coord3::new(1, 1, 1), true).count(), 26);
+    }
+
+    #[test]
+    fn cuboid_intersections() {
+        let a = Cuboid::new(Coord3::new(0, 0, 0), Coord3::new(2, 2, 2));
+        let b = Cuboid::new(Coord3::new(3, 2, 2), Coord3::new(2, 5, 5));
+        assert_eq!(a.volume(), 27);
+        assert_eq!(
+            a.intersection(&b),
+            Some(Cuboid::new(Coord3::new(2, 2, 2), Coord3::new(2, 2, 2)))
+        );
+        assert!(!a.intersects(&b.translate(Coord3::new(1, 0, 0))));
+        assert_eq!(a.coords().count(), 27);
+        assert_eq!(a.bottom_face(Axis::Z).volume(), 9);
+    }
+
+    #[test]
+    fn slices() {
+        let mut g = Grid3::init_with_size('.', 2, 3, 4);
+        g[Coord3::new(1, 2, 3)] = '#';
+        let z = g.slice(Axis::Z, 3);
+        assert_eq!((z.width(), z.height()), (2, 3));
+        let y = g.slice(Axis::Y, 2);
+        assert_eq!((y.width(), y.height()), (2, 4));
+        let x = g.slice(Axis::X, 1);
+        assert_eq!((x.width(), x.height()), (3, 4));
+        assert_eq!(x.iter().filter(|(_, c)| **c == '#').count(), 1);
+        assert_eq!(
+            g.slice(Axis::X, 0)
+                .iter()
+                .filter(|(_, c)| **c == '#')
+                .count(),
+            0
+        );
+    }
+}
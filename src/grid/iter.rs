@@ -0,0 +1,233 @@
+use std::{iter::Enumerate, vec};
+
+use super::{Coord, Grid};
+
+/// Iterates over every cell of a grid along with its coordinate, row by row
+pub struct GridIter<'a, T> {
+    grid: &'a Grid<T>,
+    coord: Coord,
+}
+
+impl<'a, T> Iterator for GridIter<'a, T> {
+    type Item = (Coord, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.coord.0 >= self.grid.width() as i64 {
+            self.coord = Coord::new(0, self.coord.1 + 1);
+        }
+        if self.coord.1 >= self.grid.height() as i64 {
+            None
+        } else {
+            let coord = self.coord;
+            let ret = &self.grid[self.coord];
+            self.coord = Coord::new(self.coord.0 + 1, self.coord.1);
+
+            Some((coord, ret))
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn iter(&self) -> GridIter<'_, T> {
+        GridIter {
+            grid: self,
+            coord: Coord::new(0, 0),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Coord, &mut T)> {
+        self.g.iter_mut().enumerate().flat_map(|(y, row)| {
+            row.iter_mut()
+                .enumerate()
+                .map(move |(x, item)| (Coord(x as i64, y as i64), item))
+        })
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.g[y]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.g.iter().map(|row| row.as_slice())
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.g.iter().map(move |row| &row[x])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width()).map(|x| self.column(x))
+    }
+
+    /// The neighbours of `coord` which are inside the grid
+    pub fn neighbours(&self, coord: Coord, diagonals: bool) -> impl Iterator<Item = (Coord, &T)> {
+        coord
+            .get_neighbours(diagonals)
+            .into_iter()
+            .filter(|x| self.in_bounds(*x))
+            .map(|x| (x, &self[x]))
+    }
+
+    pub fn find_all<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = Coord> + 'a
+    where
+        F: Fn(&T) -> bool + 'a,
+    {
+        self.iter()
+            .filter(move |(_, item)| predicate(item))
+            .map(|(coord, _)| coord)
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        self.rows().map(|row| row.iter().map(&f)).collect()
+    }
+
+    /// Combine two grids of the same size cell by cell
+    pub fn zip_with<U, V, F>(&self, other: &Grid<U>, f: F) -> Grid<V>
+    where
+        F: Fn(&T, &U) -> V,
+    {
+        assert_eq!(
+            (self.width(), self.height()),
+            (other.width(), other.height()),
+            "Can only zip grids of the same size"
+        );
+        self.rows()
+            .zip(other.rows())
+            .map(|(a, b)| a.iter().zip(b).map(|(a, b)| f(a, b)))
+            .collect()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
+    type Item = (Coord, &'a T);
+    type IntoIter = GridIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Grid<T> {
+    type Item = (Coord, &'a mut T);
+    type IntoIter = Box<dyn Iterator<Item = Self::Item> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter_mut())
+    }
+}
+
+/// Owning iterator over every cell of a grid along with its coordinate, row by row
+pub struct GridIntoIter<T> {
+    rows: Enumerate<vec::IntoIter<Vec<T>>>,
+    row: Option<(usize, Enumerate<vec::IntoIter<T>>)>,
+}
+
+impl<T> Iterator for GridIntoIter<T> {
+    type Item = (Coord, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (y, row) = self.row.as_mut()?;
+            if let Some((x, item)) = row.next() {
+                return Some((Coord(x as i64, *y as i64), item));
+            }
+            self.row = self
+                .rows
+                .next()
+                .map(|(y, row)| (y, row.into_iter().enumerate()));
+        }
+    }
+}
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = (Coord, T);
+    type IntoIter = GridIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut rows = self.g.into_iter().enumerate();
+        let row = rows.next().map(|(y, row)| (y, row.into_iter().enumerate()));
+        GridIntoIter { rows, row }
+    }
+}
+
+/// Collects rows into a grid
+impl<T, R> FromIterator<R> for Grid<T>
+where
+    R: IntoIterator<Item = T>,
+{
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        Grid {
+            g: iter
+                .into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Coord, Grid};
+
+    #[test]
+    fn rows_and_columns() {
+        let g = Grid::from_string("abc\ndef", false);
+        assert_eq!(
+            g.rows().collect::<Vec<_>>(),
+            vec![&['a', 'b', 'c'], &['d', 'e', 'f']]
+        );
+        let columns: Vec<String> = g.columns().map(|c| c.collect()).collect();
+        assert_eq!(columns, vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn neighbours_stay_in_bounds() {
+        let g = Grid::from_string("abc\ndef", false);
+        let mut n: Vec<char> = g.neighbours(Coord::new(0, 0), true).map(|x| *x.1).collect();
+        n.sort();
+        assert_eq!(n, vec!['b', 'd', 'e']);
+        assert_eq!(g.neighbours(Coord::new(1, 1), false).count(), 3);
+    }
+
+    #[test]
+    fn map_zip_and_collect() {
+        let g = Grid::from_string("#.\n.#", false);
+        let mut bools = g.map(|x| x == &'#');
+        for (coord, b) in &mut bools {
+            if coord == Coord::new(1, 0) {
+                *b = true;
+            }
+        }
+        let zipped = g.zip_with(&bools, |c, b| if *b { *c } else { ' ' });
+        assert_eq!(zipped, Grid::from_string("#.\n #", false));
+        assert_eq!(g.find_all(|x| x == &'.').collect::<Vec<_>>().len(), 2);
+        let collected: Grid<char> = "ab\ncd".lines().map(|l| l.chars()).collect();
+        assert_eq!(
+            collected.into_iter().map(|(_, c)| c).collect::<String>(),
+            "abcd"
+        );
+    }
+
+    #[test]
+    fn find_all_with_borrowed_target() {
+        let g = Grid::from_string("ab\nba", false);
+        let target = String::from("b");
+        let found: Vec<Coord> = g.find_all(|c| target.starts_with(*c)).collect();
+        assert_eq!(found, vec![Coord::new(1, 0), Coord::new(0, 1)]);
+    }
+
+    #[test]
+    fn empty_grid() {
+        let g: Grid<char> = Grid::default();
+        assert_eq!(g.iter().count(), 0);
+        assert_eq!(g.columns().count(), 0);
+        assert_eq!(g.neighbours(Coord::new(0, 0), false).count(), 0);
+        assert_eq!(g.find_all(|_| true).count(), 0);
+        let collected: Grid<char> = Vec::<Vec<char>>::new().into_iter().collect();
+        assert_eq!(collected.width(), 0);
+        assert_eq!(collected, g);
+    }
+}
@@ -0,0 +1,119 @@
+use super::{Coord, Direction, Grid};
+
+/// Summary of one connected region found by `Grid::label_regions`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Region {
+    pub label: usize,
+    pub cells: usize,
+    /// Smallest and largest coordinates in the region, inclusive
+    pub bounds: (Coord, Coord),
+    /// Number of cell edges bordering another region or the outside of the grid
+    pub perimeter: usize,
+    pub touches_border: bool,
+}
+
+impl<T> Grid<T> {
+    /// Split the grid into connected regions, where adjacent cells `a` and `b` belong to the
+    /// same region if `connected(a, b)`. Returns a grid of region labels, which index into the
+    /// list of regions, in the order they are first found scanning the grid.
+    pub fn label_regions<F>(&self, diagonals: bool, connected: F) -> (Grid<usize>, Vec<Region>)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut labels: Grid<Option<usize>> =
+            Grid::init_with_size(None, self.width(), self.height());
+        let mut regions = vec![];
+        for (start, _) in self.iter() {
+            if labels[start].is_some() {
+                continue;
+            }
+            let label = regions.len();
+            let mut region = Region {
+                label,
+                cells: 0,
+                bounds: (start, start),
+                perimeter: 0,
+                touches_border: false,
+            };
+            labels[start] = Some(label);
+            let mut stack = vec![start];
+            while let Some(coord) = stack.pop() {
+                region.cells += 1;
+                region.bounds = (
+                    Coord(
+                        region.bounds.0 .0.min(coord.0),
+                        region.bounds.0 .1.min(coord.1),
+                    ),
+                    Coord(
+                        region.bounds.1 .0.max(coord.0),
+                        region.bounds.1 .1.max(coord.1),
+                    ),
+                );
+                for dir in Direction::all() {
+                    match coord.translate(dir, self) {
+                        Some(next) if connected(&self[coord], &self[next]) => (),
+                        Some(_) => region.perimeter += 1,
+                        None => {
+                            region.perimeter += 1;
+                            region.touches_border = true;
+                        }
+                    }
+                }
+                for next in coord.get_neighbours(diagonals) {
+                    if self.in_bounds(next)
+                        && labels[next].is_none()
+                        && connected(&self[coord], &self[next])
+                    {
+                        labels[next] = Some(label);
+                        stack.push(next);
+                    }
+                }
+            }
+            regions.push(region);
+        }
+        let labels = Grid {
+            g: labels
+                .g
+                .into_iter()
+                .map(|row| row.into_iter().map(Option::unwrap).collect())
+                .collect(),
+        };
+        (labels, regions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Coord, Grid};
+
+    #[test]
+    fn four_and_eight_connectivity() {
+        let g = Grid::from_string("#..\n.#.\n..#", false);
+        let (labels, regions) = g.label_regions(false, |a, b| a == b);
+        assert_eq!(regions.len(), 5);
+        assert_eq!(labels[Coord::new(1, 0)], labels[Coord::new(2, 1)]);
+        assert_ne!(labels[Coord::new(1, 0)], labels[Coord::new(0, 2)]);
+        assert_ne!(labels[Coord::new(0, 0)], labels[Coord::new(1, 1)]);
+
+        let (labels, regions) = g.label_regions(true, |a, b| a == b);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(labels[Coord::new(0, 0)], labels[Coord::new(2, 2)]);
+        assert_eq!(regions[0].cells, 3);
+        assert_eq!(regions[0].perimeter, 12);
+        assert_eq!(regions[1].cells, 6);
+    }
+
+    #[test]
+    fn region_metrics() {
+        let g = Grid::from_string(".....\n.##..\n.##..\n.....", false);
+        let (_, regions) = g.label_regions(false, |a, b| a == b);
+        assert_eq!(regions.len(), 2);
+        let block = &regions[1];
+        assert_eq!(block.cells, 4);
+        assert_eq!(block.bounds, (Coord::new(1, 1), Coord::new(2, 2)));
+        assert_eq!(block.perimeter, 8);
+        assert!(!block.touches_border);
+        assert_eq!(regions[0].perimeter, 18 + 8);
+        assert!(regions[0].touches_border);
+    }
+}
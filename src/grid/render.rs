@@ -0,0 +1,154 @@
+use std::fmt::Write;
+
+use super::{Coord, Grid};
+
+pub type Colour = (u8, u8, u8);
+
+/// Something drawn over the top of the cell colours
+pub enum Overlay<'a> {
+    /// A route through the grid, drawn as a line between cell centres in SVG and by
+    /// recolouring each cell in PPM
+    Path(&'a [Coord], Colour),
+    /// Individual cells to pick out, outlined in SVG and recoloured in PPM
+    Highlight(&'a [Coord], Colour),
+}
+
+fn hex(colour: Colour) -> String {
+    format!("#{:02x}{:02x}{:02x}", colour.0, colour.1, colour.2)
+}
+
+impl<T> Grid<T> {
+    /// Render as a binary PPM image where each cell is a `scale` by `scale` block of pixels,
+    /// with row 0 at the top as in `Display`
+    pub fn to_ppm<F>(&self, colour: F, overlays: &[Overlay], scale: usize) -> Vec<u8>
+    where
+        F: Fn(&T) -> Colour,
+    {
+        let mut colours = Grid {
+            g: self
+                .g
+                .iter()
+                .map(|row| row.iter().map(&colour).collect())
+                .collect(),
+        };
+        for overlay in overlays {
+            let (coords, c) = match overlay {
+                Overlay::Path(coords, c) | Overlay::Highlight(coords, c) => (coords, c),
+            };
+            for coord in coords.iter().filter(|x| self.in_bounds(**x)) {
+                colours[*coord] = *c;
+            }
+        }
+
+        let mut out = format!(
+            "P6\n{} {}\n255\n",
+            self.width() * scale,
+            self.height() * scale
+        )
+        .into_bytes();
+        for row in colours.g.iter() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|c| [c.0, c.1, c.2].repeat(scale))
+                .collect();
+            for _ in 0..scale {
+                out.extend_from_slice(&line);
+            }
+        }
+        out
+    }
+
+    /// Render as an SVG document where each cell is a `cell_size` square, with row 0 at the
+    /// top as in `Display`
+    pub fn to_svg<F>(&self, colour: F, overlays: &[Overlay], cell_size: usize) -> String
+    where
+        F: Fn(&T) -> Colour,
+    {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n",
+            self.width() * cell_size,
+            self.height() * cell_size
+        );
+        for (coord, item) in self.iter() {
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{cell_size}\" height=\"{cell_size}\" fill=\"{}\"/>",
+                coord.0 as usize * cell_size,
+                coord.1 as usize * cell_size,
+                hex(colour(item))
+            );
+        }
+        let centre = |c: i64| c as f64 * cell_size as f64 + cell_size as f64 / 2.0;
+        for overlay in overlays {
+            match overlay {
+                Overlay::Path(coords, c) => {
+                    let points: Vec<String> = coords
+                        .iter()
+                        .map(|x| format!("{},{}", centre(x.0), centre(x.1)))
+                        .collect();
+                    let _ = writeln!(
+                        svg,
+                        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+                        points.join(" "),
+                        hex(*c),
+                        (cell_size as f64 / 4.0).max(1.0)
+                    );
+                }
+                Overlay::Highlight(coords, c) => {
+                    for coord in coords.iter() {
+                        let _ = writeln!(
+                            svg,
+                            "<rect x=\"{}\" y=\"{}\" width=\"{cell_size}\" height=\"{cell_size}\" fill=\"none\" stroke=\"{}\"/>",
+                            coord.0 * cell_size as i64,
+                            coord.1 * cell_size as i64,
+                            hex(*c)
+                        );
+                    }
+                }
+            }
+        }
+        svg += "</svg>\n";
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{render::Overlay, Coord, Grid};
+
+    fn colour(c: &char) -> (u8, u8, u8) {
+        if c == &'#' {
+            (0, 0, 0)
+        } else {
+            (255, 255, 255)
+        }
+    }
+
+    #[test]
+    fn ppm_pixels() {
+        let g = Grid::from_string("#.\n..", false);
+        let ppm = g.to_ppm(
+            colour,
+            &[Overlay::Highlight(&[Coord::new(1, 1)], (255, 0, 0))],
+            2,
+        );
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 4 * 4 * 3);
+        assert_eq!(&pixels[0..6], &[0, 0, 0, 0, 0, 0]);
+        assert_eq!(&pixels[6..9], &[255, 255, 255]);
+        assert_eq!(&pixels[pixels.len() - 3..], &[255, 0, 0]);
+    }
+
+    #[test]
+    fn svg_elements() {
+        let g = Grid::from_string("#.\n..", false);
+        let path = [Coord::new(0, 1), Coord::new(1, 1)];
+        let svg = g.to_svg(colour, &[Overlay::Path(&path, (255, 0, 0))], 10);
+        assert_eq!(svg.matches("<rect").count(), 4);
+        assert!(svg.contains("fill=\"#000000\""));
+        assert!(svg.contains("points=\"5,15 15,15\""));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
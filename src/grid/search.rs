@@ -0,0 +1,236 @@
+use std::{collections::VecDeque, hash::Hash};
+
+use pathfinding::prelude::{astar, dijkstra};
+
+use super::{Coord, Direction, Grid};
+
+/// A position along with the direction it was entered from and how many steps have been taken
+/// in that direction
+pub type RunState = (Coord, Option<Direction>, u32);
+
+impl<T> Grid<T> {
+    /// Breadth first search out from every coordinate in `sources` at once, moving only onto
+    /// cells that satisfy `passable`. Each cell holds the number of steps to the nearest source,
+    /// or `None` if it can't be reached.
+    pub fn get_distances_from<F>(&self, sources: &[Coord], passable: F) -> Grid<Option<i64>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut distances = Grid::init_with_size(None, self.width(), self.height());
+        let mut queue = VecDeque::new();
+        for source in sources {
+            if self.in_bounds(*source) && distances[*source].is_none() {
+                distances[*source] = Some(0);
+                queue.push_back((*source, 0));
+            }
+        }
+        while let Some((coord, steps)) = queue.pop_front() {
+            for dir in Direction::all() {
+                if let Some(next) = coord.translate(dir, self) {
+                    if distances[next].is_none() && passable(&self[next]) {
+                        distances[next] = Some(steps + 1);
+                        queue.push_back((next, steps + 1));
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    /// Cheapest route from `start` to `end` moving between adjacent passable cells, where
+    /// `cost` gives the price of entering a cell. Returns the route, including both ends,
+    /// along with its total cost.
+    pub fn shortest_path<P, C>(
+        &self,
+        start: Coord,
+        end: Coord,
+        passable: P,
+        cost: C,
+    ) -> Option<(Vec<Coord>, i64)>
+    where
+        P: Fn(&T) -> bool,
+        C: Fn(&T) -> i64,
+    {
+        dijkstra(
+            &start,
+            |coord| {
+                Direction::all()
+                    .into_iter()
+                    .filter_map(|dir| coord.translate(dir, self))
+                    .filter(|next| passable(&self[*next]))
+                    .map(|next| (next, cost(&self[next])))
+                    .collect::<Vec<_>>()
+            },
+            |coord| coord == &end,
+        )
+    }
+
+    /// Like `shortest_path` but guided by `heuristic`, an estimate of the remaining cost from a
+    /// coordinate to `end`. The route found is only the cheapest if the estimate never goes over
+    /// the real cost.
+    pub fn shortest_path_astar<P, C, H>(
+        &self,
+        start: Coord,
+        end: Coord,
+        passable: P,
+        cost: C,
+        heuristic: H,
+    ) -> Option<(Vec<Coord>, i64)>
+    where
+        P: Fn(&T) -> bool,
+        C: Fn(&T) -> i64,
+        H: Fn(Coord) -> i64,
+    {
+        astar(
+            &start,
+            |coord| {
+                Direction::all()
+                    .into_iter()
+                    .filter_map(|dir| coord.translate(dir, self))
+                    .filter(|next| passable(&self[*next]))
+                    .map(|next| (next, cost(&self[next])))
+                    .collect::<Vec<_>>()
+            },
+            |coord| heuristic(*coord),
+            |coord| coord == &end,
+        )
+    }
+
+    /// Cheapest route through states of the caller's choosing, such as a position along with a
+    /// direction or step count. `successors` gives the states reachable from a state and the
+    /// cost of each move.
+    pub fn shortest_path_over_states<S, I, N, E>(
+        &self,
+        start: S,
+        successors: N,
+        is_end: E,
+    ) -> Option<(Vec<S>, i64)>
+    where
+        S: Clone + Eq + Hash,
+        I: IntoIterator<Item = (S, i64)>,
+        N: Fn(&Self, &S) -> I,
+        E: Fn(&S) -> bool,
+    {
+        dijkstra(&start, |state| successors(self, state), is_end)
+    }
+
+    /// Like `shortest_path` but the state also tracks the direction of travel and how many
+    /// steps have been taken in it. A move must go at least `min_run` steps in a direction
+    /// before turning or stopping at `end`, may go at most `max_run`, and can never reverse.
+    pub fn shortest_path_with_runs<P, C>(
+        &self,
+        start: Coord,
+        end: Coord,
+        min_run: u32,
+        max_run: u32,
+        passable: P,
+        cost: C,
+    ) -> Option<(Vec<RunState>, i64)>
+    where
+        P: Fn(&T) -> bool,
+        C: Fn(&T) -> i64,
+    {
+        self.shortest_path_over_states(
+            (start, None, 0),
+            |grid, (coord, dir, run): &RunState| {
+                Direction::all()
+                    .into_iter()
+                    .filter(|next_dir| match dir {
+                        None => true,
+                        Some(d) if d == next_dir => *run < max_run,
+                        Some(d) => *d != next_dir.opposite() && *run >= min_run,
+                    })
+                    .filter_map(|next_dir| {
+                        let next = coord.translate(next_dir, grid)?;
+                        if !passable(&grid[next]) {
+                            return None;
+                        }
+                        let next_run = if *dir == Some(next_dir) { run + 1 } else { 1 };
+                        Some(((next, Some(next_dir), next_run), cost(&grid[next])))
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |(coord, _, run)| coord == &end && *run >= min_run,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Coord, Grid};
+
+    #[test]
+    fn distances_from_two_sources() {
+        let g = Grid::from_string("....\n.##.\n#...", false);
+        let distances = g.get_distances_from(&[Coord::new(0, 0), Coord::new(3, 2)], |x| x == &'.');
+        assert_eq!(distances[Coord::new(1, 2)], Some(2));
+        assert_eq!(distances[Coord::new(0, 1)], Some(1));
+        assert_eq!(distances[Coord::new(1, 1)], None);
+        assert_eq!(distances[Coord::new(0, 2)], None);
+    }
+
+    #[test]
+    fn shortest_path_avoids_walls() {
+        let g = Grid::from_string("..#\n#..\n...", false);
+        let (path, cost) = g
+            .shortest_path(Coord::new(0, 0), Coord::new(2, 2), |x| x != &'#', |_| 1)
+            .unwrap();
+        assert_eq!(cost, 4);
+        assert_eq!(path.len(), 5);
+        assert_eq!(path[0], Coord::new(0, 0));
+        assert_eq!(path[4], Coord::new(2, 2));
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        let g = Grid::from_string_i64(include_str!("../../input/day_17/example"));
+        let (start, end) = (Coord::new(0, 0), Coord::new(12, 12));
+        let manhattan = |c: Coord| (end.x() - c.x()).abs() + (end.y() - c.y()).abs();
+        let astar = g.shortest_path_astar(start, end, |_| true, |x| *x, manhattan);
+        let dijkstra = g.shortest_path(start, end, |_| true, |x| *x);
+        assert_eq!(astar.map(|x| x.1), dijkstra.map(|x| x.1));
+        assert!(g
+            .shortest_path_astar(start, end, |_| false, |x| *x, |_| 0)
+            .is_none());
+    }
+
+    #[test]
+    fn search_over_custom_states() {
+        // Walk right along one row, where the state is the column and how many steps are left
+        // before a rest costing 5 is needed
+        let g = Grid::from_string("......", false);
+        let (path, cost) = g
+            .shortest_path_over_states(
+                (0_i64, 2_u32),
+                |grid, (x, left)| {
+                    let mut next = vec![];
+                    if *left > 0 && grid.in_bounds(Coord::new(x + 1, 0)) {
+                        next.push(((x + 1, left - 1), 1));
+                    }
+                    next.push(((*x, 2), 5));
+                    next
+                },
+                |(x, _)| *x == 5,
+            )
+            .unwrap();
+        assert_eq!(cost, 5 + 2 * 5);
+        assert_eq!(path.first(), Some(&(0, 2)));
+        assert_eq!(path.last().map(|x| x.0), Some(5));
+    }
+
+    #[test]
+    fn runs_on_day_17_examples() {
+        let g = Grid::from_string_i64(include_str!("../../input/day_17/example"));
+        let start = Coord::new(0, g.height() as i64 - 1);
+        let end = Coord::new(g.width() as i64 - 1, 0);
+        let res = g.shortest_path_with_runs(start, end, 1, 3, |_| true, |x| *x);
+        assert_eq!(res.unwrap().1, 102);
+        let res = g.shortest_path_with_runs(start, end, 4, 10, |_| true, |x| *x);
+        assert_eq!(res.unwrap().1, 94);
+        let g = Grid::from_string_i64(include_str!("../../input/day_17/example2"));
+        let start = Coord::new(0, g.height() as i64 - 1);
+        let end = Coord::new(g.width() as i64 - 1, 0);
+        let res = g.shortest_path_with_runs(start, end, 4, 10, |_| true, |x| *x);
+        assert_eq!(res.unwrap().1, 71);
+    }
+}
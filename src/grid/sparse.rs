@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use super::{Coord, Grid};
+
+/// A grid with no fixed bounds. Only cells that have been written to are stored, every other
+/// cell reads as `default`, and the bounding box grows to cover whatever has been written.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Coord, T>,
+    default: T,
+    bounds: Option<(Coord, Coord)>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    /// The smallest and largest coordinates written to so far, inclusive
+    pub fn bounds(&self) -> Option<(Coord, Coord)> {
+        self.bounds
+    }
+
+    pub fn width(&self) -> usize {
+        self.bounds
+            .map_or(0, |(min, max)| (max.0 - min.0 + 1) as usize)
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds
+            .map_or(0, |(min, max)| (max.1 - min.1 + 1) as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.cells.get(&coord)
+    }
+
+    pub fn insert(&mut self, coord: Coord, value: T) -> Option<T> {
+        self.grow_to(coord);
+        self.cells.insert(coord, value)
+    }
+
+    pub fn remove(&mut self, coord: Coord) -> Option<T> {
+        self.cells.remove(&coord)
+    }
+
+    /// Extend the bounding box to include `coord` without storing anything there
+    pub fn grow_to(&mut self, coord: Coord) {
+        self.bounds = Some(match self.bounds {
+            None => (coord, coord),
+            Some((min, max)) => (
+                Coord(min.0.min(coord.0), min.1.min(coord.1)),
+                Coord(max.0.max(coord.0), max.1.max(coord.1)),
+            ),
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells.iter().map(|(c, v)| (*c, v))
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// Copy into a dense grid covering the bounding box. Returns the grid along with the
+    /// coordinate that its `(0, 0)` corresponds to.
+    pub fn to_grid(&self) -> (Grid<T>, Coord) {
+        let Some((min, _)) = self.bounds else {
+            return (Grid { g: vec![] }, Coord(0, 0));
+        };
+        let mut grid = Grid::init_with_size(self.default.clone(), self.width(), self.height());
+        for (coord, value) in self.iter() {
+            grid[Coord(coord.0 - min.0, coord.1 - min.1)] = value.clone();
+        }
+        (grid, min)
+    }
+}
+
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    /// Build from a dense grid, only storing the cells that differ from `default`. The bounding
+    /// box still covers the whole of `grid`.
+    pub fn from_grid(grid: &Grid<T>, default: T) -> Self {
+        let mut sparse = Self::new(default);
+        if grid.height() == 0 {
+            return sparse;
+        }
+        sparse.grow_to(Coord(0, 0));
+        sparse.grow_to(Coord(grid.width() as i64 - 1, grid.height() as i64 - 1));
+        for (coord, value) in grid.iter() {
+            if *value != sparse.default {
+                sparse.cells.insert(coord, value.clone());
+            }
+        }
+        sparse
+    }
+}
+
+impl<T> Index<Coord> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: Coord) -> &Self::Output {
+        self.cells.get(&index).unwrap_or(&self.default)
+    }
+}
+
+impl<T: Clone> IndexMut<Coord> for SparseGrid<T> {
+    fn index_mut(&mut self, index: Coord) -> &mut Self::Output {
+        self.grow_to(index);
+        self.cells
+            .entry(index)
+            .or_insert_with(|| self.default.clone())
+    }
+}
+
+impl<T: Clone + Display> Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_grid().0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Coord, Grid, SparseGrid};
+
+    #[test]
+    fn grows_with_negative_coords() {
+        let mut g = SparseGrid::new('.');
+        g[Coord::new(-2, 1)] = '#';
+        g.insert(Coord::new(1, -1), '#');
+        assert_eq!(g.bounds(), Some((Coord::new(-2, -1), Coord::new(1, 1))));
+        assert_eq!(g[Coord::new(0, 0)], '.');
+        assert_eq!(g.to_string(), "...#\n....\n#...");
+    }
+
+    #[test]
+    fn dense_round_trip() {
+        let dense = Grid::from_string("..#\n#..", false);
+        let sparse = SparseGrid::from_grid(&dense, '.');
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.to_grid(), (dense, Coord::new(0, 0)));
+    }
+}
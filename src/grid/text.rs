@@ -0,0 +1,141 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::Grid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseGridError {
+    /// There are lines but none of them have any cells, which `Display` couldn't print back
+    Empty,
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for ParseGridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseGridError::Empty => f.write_str("Grid has rows with no cells in them"),
+            ParseGridError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {} has {} characters but the first line has {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+/// Parses rows top to bottom, the inverse of `Display`. An empty string is a grid with no rows.
+impl FromStr for Grid<char> {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let g: Vec<Vec<char>> = s.lines().map(|x| x.chars().collect()).collect();
+        let Some(expected) = g.first().map(Vec::len) else {
+            return Ok(Grid::default());
+        };
+        if expected == 0 {
+            return Err(ParseGridError::Empty);
+        }
+        if let Some((line, row)) = g.iter().enumerate().find(|(_, r)| r.len() != expected) {
+            return Err(ParseGridError::RaggedRow {
+                line: line + 1,
+                expected,
+                found: row.len(),
+            });
+        }
+        Ok(Grid { g })
+    }
+}
+
+impl<T: Display + PartialEq> Grid<T> {
+    /// Print this grid and `other` side by side, followed by a third column marking each cell
+    /// that differs with `X`
+    pub fn diff(&self, other: &Grid<T>) -> String {
+        // Either grid may have no rows, so don't go through `width`
+        let width = self
+            .g
+            .iter()
+            .chain(&other.g)
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+        let cell = |g: &Grid<T>, x: usize, y: usize| {
+            g.g.get(y)
+                .and_then(|r| r.get(x))
+                .map_or(" ".to_owned(), |c| c.to_string())
+        };
+        (0..self.height().max(other.height()))
+            .map(|y| {
+                let left: String = (0..width).map(|x| cell(self, x, y)).collect();
+                let right: String = (0..width).map(|x| cell(other, x, y)).collect();
+                let changes: String = (0..width)
+                    .map(|x| {
+                        let a = self.g.get(y).and_then(|r| r.get(x));
+                        let b = other.g.get(y).and_then(|r| r.get(x));
+                        if a == b {
+                            '.'
+                        } else {
+                            'X'
+                        }
+                    })
+                    .collect();
+                format!("{}  {}  {}", left, right, changes)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{text::ParseGridError, Grid};
+
+    #[test]
+    fn round_trip() {
+        let input = "#..\n.O.\n..#";
+        let g: Grid<char> = input.parse().unwrap();
+        assert_eq!(g.to_string(), input);
+        assert_eq!(g.to_string().parse::<Grid<char>>(), Ok(g));
+
+        let empty: Grid<char> = Grid::default();
+        assert_eq!(empty.to_string(), "");
+        assert_eq!("".parse::<Grid<char>>(), Ok(empty));
+        // A row with no cells would print as nothing at all
+        assert_eq!("\n".parse::<Grid<char>>(), Err(ParseGridError::Empty));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("\n\n".parse::<Grid<char>>(), Err(ParseGridError::Empty));
+        assert_eq!(
+            "...\n..\n...".parse::<Grid<char>>(),
+            Err(ParseGridError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn diff_marks_changes() {
+        let a: Grid<char> = "ab\ncd".parse().unwrap();
+        let b: Grid<char> = "ab\nxd".parse().unwrap();
+        assert_eq!(a.diff(&b), "ab  ab  ..\ncd  xd  X.");
+    }
+
+    #[test]
+    fn diff_with_empty_grid() {
+        let a: Grid<char> = "ab".parse().unwrap();
+        let empty: Grid<char> = Grid::default();
+        assert_eq!(a.diff(&empty), "ab      XX");
+        assert_eq!(empty.diff(&a), "    ab  XX");
+        assert_eq!(empty.diff(&empty), "");
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    ops::Index,
+};
+
+use super::{Coord, Grid};
+
+/// A view over a `Grid` that repeats it forever in every direction. Any `Coord` can be
+/// indexed, and maps onto the underlying grid modulo its size.
+pub struct TiledGrid<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<'a, T> TiledGrid<'a, T> {
+    pub fn new(grid: &'a Grid<T>) -> Self {
+        Self { grid }
+    }
+
+    pub fn tile(&self) -> &Grid<T> {
+        self.grid
+    }
+
+    /// The coordinate within the underlying grid that `coord` refers to
+    pub fn wrap(&self, coord: Coord) -> Coord {
+        Coord(
+            coord.0.rem_euclid(self.grid.width() as i64),
+            coord.1.rem_euclid(self.grid.height() as i64),
+        )
+    }
+
+    /// Which copy of the tile `coord` lies in, the original grid being `(0, 0)`
+    pub fn tile_of(&self, coord: Coord) -> Coord {
+        Coord(
+            coord.0.div_euclid(self.grid.width() as i64),
+            coord.1.div_euclid(self.grid.height() as i64),
+        )
+    }
+
+    /// Breadth first search from `start` over every cell satisfying `passable`, stopping after
+    /// `max_steps`. Returns the number of steps needed to reach each visited coordinate.
+    pub fn get_steps_from<F>(
+        &self,
+        start: Coord,
+        max_steps: i64,
+        passable: F,
+    ) -> HashMap<Coord, i64>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut steps = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((coord, s)) = queue.pop_front() {
+            if s >= max_steps {
+                continue;
+            }
+            for next in coord.get_neighbours(false) {
+                if !steps.contains_key(&next) && passable(&self[next]) {
+                    steps.insert(next, s + 1);
+                    queue.push_back((next, s + 1));
+                }
+            }
+        }
+        steps
+    }
+
+    /// Every coordinate connected to `start` through cells satisfying `passable`, only
+    /// considering copies of the tile at most `tile_radius` tiles away from the original.
+    pub fn flood_fill<F>(&self, start: Coord, tile_radius: i64, passable: F) -> HashSet<Coord>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut filled = HashSet::new();
+        let mut stack = vec![start];
+        while let Some(coord) = stack.pop() {
+            let tile = self.tile_of(coord);
+            if tile.0.abs() > tile_radius
+                || tile.1.abs() > tile_radius
+                || !passable(&self[coord])
+                || !filled.insert(coord)
+            {
+                continue;
+            }
+            stack.extend(coord.get_neighbours(false));
+        }
+        filled
+    }
+}
+
+impl<'a, T> Index<Coord> for TiledGrid<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: Coord) -> &Self::Output {
+        &self.grid[self.wrap(index)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Coord, Grid, TiledGrid};
+
+    #[test]
+    fn wraps_negative_coords() {
+        let g = Grid::from_string("ab\ncd", false);
+        let tiled = TiledGrid::new(&g);
+        assert_eq!(tiled[Coord::new(-1, -1)], 'd');
+        assert_eq!(tiled[Coord::new(4, 3)], 'c');
+        assert_eq!(tiled.tile_of(Coord::new(-1, 2)), Coord::new(-1, 1));
+        assert_eq!(tiled.tile_of(Coord::new(1, 1)), Coord::new(0, 0));
+    }
+
+    #[test]
+    fn steps_cross_tile_edges() {
+        // With no walls the reachable cells form a diamond regardless of the tile size
+        let g = Grid::from_string("...\n.S.\n...", false);
+        let tiled = TiledGrid::new(&g);
+        for steps in [1, 2, 6, 11] {
+            let count = tiled
+                .get_steps_from(Coord::new(1, 1), steps, |x| x != &'#')
+                .values()
+                .filter(|s| *s % 2 == steps % 2)
+                .count();
+            assert_eq!(count as i64, (steps + 1) * (steps + 1));
+        }
+    }
+
+    #[test]
+    fn flood_fill_is_bounded() {
+        let g = Grid::from_string(".#\n##", false);
+        let tiled = TiledGrid::new(&g);
+        assert_eq!(
+            tiled.flood_fill(Coord::new(0, 0), 5, |x| x == &'.').len(),
+            1
+        );
+        let g = Grid::from_string("..\n..", false);
+        let tiled = TiledGrid::new(&g);
+        assert_eq!(
+            tiled.flood_fill(Coord::new(0, 0), 1, |x| x == &'.').len(),
+            36
+        );
+    }
+}
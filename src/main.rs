@@ -31,6 +31,7 @@ use day_24::Task24;
 
 use crate::{day_01::Task1, day_02::Task2};
 
+mod cycle;
 mod day_01;
 mod day_02;
 mod day_03;
@@ -56,6 +57,8 @@ mod day_22;
 mod day_23;
 mod day_24;
 mod grid;
+mod scanner;
+mod sequence;
 
 pub trait TaskCompleter {
     fn get_name_auto(&self) -> String {
@@ -0,0 +1,88 @@
+/// Finds the first and last occurrence of any of a fixed set of tokens in a line, where
+/// tokens are allowed to overlap such as "one" and "eight" in "oneight"
+pub struct TokenScanner<V> {
+    tokens: Vec<(String, V)>,
+}
+
+const DIGIT_WORDS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+impl TokenScanner<u32> {
+    /// The digits 0 to 9
+    pub fn digits() -> Self {
+        Self::new((0..10).map(|x| (x.to_string(), x)))
+    }
+
+    /// The digits 0 to 9, written either as numerals or as lowercase English words
+    pub fn digits_and_words() -> Self {
+        Self::new(
+            (0..10)
+                .map(|x| (x.to_string(), x))
+                .chain((0..10).map(|x| (DIGIT_WORDS[x as usize].to_owned(), x))),
+        )
+    }
+}
+
+impl<V: Copy> TokenScanner<V> {
+    pub fn new<S, I>(tokens: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (S, V)>,
+    {
+        let tokens: Vec<(String, V)> = tokens.into_iter().map(|(s, v)| (s.into(), v)).collect();
+        assert!(
+            tokens.iter().all(|(s, _)| !s.is_empty()),
+            "Tokens can't be empty"
+        );
+        Self { tokens }
+    }
+
+    fn token_at(&self, line: &str, index: usize) -> Option<V> {
+        self.tokens
+            .iter()
+            .find(|(s, _)| line[index..].starts_with(s.as_str()))
+            .map(|(_, v)| *v)
+    }
+
+    /// The value of the token starting earliest in the line
+    pub fn first(&self, line: &str) -> Option<V> {
+        line.char_indices()
+            .find_map(|(i, _)| self.token_at(line, i))
+    }
+
+    /// The value of the token starting latest in the line
+    pub fn last(&self, line: &str) -> Option<V> {
+        line.char_indices()
+            .rev()
+            .find_map(|(i, _)| self.token_at(line, i))
+    }
+
+    /// Both ends of the line, which will be the same token if only one is present
+    pub fn first_and_last(&self, line: &str) -> Option<(V, V)> {
+        Some((self.first(line)?, self.last(line)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scanner::TokenScanner;
+
+    #[test]
+    fn overlapping_words() {
+        let scanner = TokenScanner::digits_and_words();
+        assert_eq!(scanner.first_and_last("eightwothree"), Some((8, 3)));
+        assert_eq!(scanner.first_and_last("xtwone3four"), Some((2, 4)));
+        assert_eq!(scanner.first_and_last("zoneight234"), Some((1, 4)));
+        assert_eq!(scanner.first_and_last("oneight"), Some((1, 8)));
+        assert_eq!(scanner.first_and_last("7pqrstsixteen"), Some((7, 6)));
+        assert_eq!(scanner.first_and_last("abc"), None);
+    }
+
+    #[test]
+    fn custom_vocabulary() {
+        let scanner = TokenScanner::new([("un", 1), ("deux", 2), ("trois", 3), ("été", 4)]);
+        assert_eq!(scanner.first_and_last("étédeuxtroisun"), Some((4, 1)));
+        assert_eq!(scanner.first_and_last("xxdeuxx"), Some((2, 2)));
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use num::Integer;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SequenceError {
+    Empty,
+    /// Taking differences never gave a row of zeros, so the values don't pin down a polynomial
+    NotPolynomial,
+}
+
+impl Display for SequenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceError::Empty => f.write_str("Sequence has no values"),
+            SequenceError::NotPolynomial => {
+                f.write_str("Differences never reach all zeros, sequence isn't a polynomial")
+            }
+        }
+    }
+}
+
+/// A sequence whose values are a polynomial in their position, kept in Newton's forward
+/// difference form so any term can be found exactly with integer arithmetic
+#[derive(Debug, PartialEq, Clone)]
+pub struct Polynomial<T> {
+    // The first value of each row of the difference table, down to the last non-zero row
+    differences: Vec<T>,
+    len: usize,
+}
+
+impl<T> Polynomial<T>
+where
+    T: Integer + Clone + From<i64>,
+{
+    /// Fit the polynomial through `values`, which are taken to be at positions 0, 1, 2, ...
+    pub fn fit(values: &[T]) -> Result<Self, SequenceError> {
+        if values.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+        let mut differences = vec![];
+        let mut row = values.to_vec();
+        while row.iter().any(|x| !x.is_zero()) {
+            if row.len() == 1 {
+                return Err(SequenceError::NotPolynomial);
+            }
+            differences.push(row[0].clone());
+            row = row
+                .windows(2)
+                .map(|x| x[1].clone() - x[0].clone())
+                .collect();
+        }
+        Ok(Self {
+            differences,
+            len: values.len(),
+        })
+    }
+
+    /// The degree of the polynomial, with a sequence of zeros counted as degree 0
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    /// The value at any position, including before the start or past the end of the values it
+    /// was fitted to
+    pub fn value_at(&self, position: i64) -> T {
+        // Newton's forward difference formula, sum of `differences[k] * (position choose k)`.
+        // Each binomial is built from the last, and the division is always exact.
+        let mut value = T::zero();
+        let mut binomial = T::one();
+        for (k, difference) in self.differences.iter().enumerate() {
+            if k > 0 {
+                binomial = binomial * T::from(position - k as i64 + 1) / T::from(k as i64);
+            }
+            value = value + difference.clone() * binomial.clone();
+        }
+        value
+    }
+
+    /// The value `steps` places after the last value
+    pub fn ahead(&self, steps: i64) -> T {
+        self.value_at(self.len as i64 - 1 + steps)
+    }
+
+    /// The value `steps` places before the first value
+    pub fn behind(&self, steps: i64) -> T {
+        self.value_at(-steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+
+    use crate::sequence::{Polynomial, SequenceError};
+
+    #[test]
+    fn example_sequences() {
+        let cases: [([i128; 6], i128, i128); 3] = [
+            ([0, 3, 6, 9, 12, 15], 18, -3),
+            ([1, 3, 6, 10, 15, 21], 28, 0),
+            ([10, 13, 16, 21, 30, 45], 68, 5),
+        ];
+        for (values, next, previous) in cases {
+            let p = Polynomial::fit(&values).unwrap();
+            assert_eq!(p.ahead(1), next);
+            assert_eq!(p.behind(1), previous);
+            for (i, x) in values.iter().enumerate() {
+                assert_eq!(p.value_at(i as i64), *x);
+            }
+        }
+        assert_eq!(
+            Polynomial::<i128>::fit(&[10, 13, 16, 21, 30, 45])
+                .unwrap()
+                .degree(),
+            3
+        );
+        assert_eq!(Polynomial::<i128>::fit(&[4, 4]).unwrap().degree(), 0);
+        assert_eq!(Polynomial::<i128>::fit(&[0, 0, 0]).unwrap().ahead(5), 0);
+    }
+
+    #[test]
+    fn far_from_the_values() {
+        let cubes: Vec<i128> = (0..5).map(|x| x * x * x - 2 * x).collect();
+        let p = Polynomial::fit(&cubes).unwrap();
+        assert_eq!(p.degree(), 3);
+        assert_eq!(p.value_at(1_000_000), 1_000_000_i128.pow(3) - 2_000_000);
+        assert_eq!(p.behind(7), -343 + 14);
+
+        let squares: Vec<BigInt> = (0..4).map(|x| BigInt::from(x * x)).collect();
+        let p = Polynomial::fit(&squares).unwrap();
+        let far = BigInt::from(i64::MAX - 3);
+        assert_eq!(p.ahead(i64::MAX - 6), &far * &far);
+    }
+
+    #[test]
+    fn not_polynomial() {
+        assert_eq!(
+            Polynomial::<i128>::fit(&[1, 2, 4, 8]),
+            Err(SequenceError::NotPolynomial)
+        );
+        assert_eq!(
+            Polynomial::<i128>::fit(&[1]),
+            Err(SequenceError::NotPolynomial)
+        );
+        assert_eq!(Polynomial::<i128>::fit(&[]), Err(SequenceError::Empty));
+    }
+}